@@ -0,0 +1,180 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::sysvar::slot_hashes;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::state::*;
+use crate::error::*;
+use crate::ownership::{generate_transaction_id, read_ledger_anchor, validate_file_hash};
+use crate::subscription::apply_claim_credits;
+use crate::service::{
+    charge_service_fee, get_batch_claim_price, get_service_price, validate_payment_accounts, SERVICE_CLAIM,
+};
+
+// Merkle 树哈希域分隔前缀，防止叶子与内部节点混淆
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+// 创建批量确权记录：一次支付锚定一批文件哈希的 Merkle 根
+pub fn create_batch_claim(
+    ctx: Context<CreateBatchClaim>,
+    merkle_root: [u8; 32],
+    count: u32,
+    max_price: u64,
+) -> Result<()> {
+    require!(merkle_root != [0u8; 32], MscError::InvalidMerkleProof);
+    
+    validate_payment_accounts(
+        &ctx.accounts.config,
+        &ctx.accounts.service_config,
+        &ctx.accounts.user_token_account,
+        &ctx.accounts.treasury_token_account,
+        ctx.accounts.user.key(),
+    )?;
+    let clock = Clock::get()?;
+    let unit_price = get_service_price(&ctx.accounts.service_config, SERVICE_CLAIM)?;
+    
+    // 传入订阅账户时按批量条数扣减订阅额度，不再收取确权费用
+    let price = apply_claim_credits(
+        get_batch_claim_price(unit_price, count)?,
+        ctx.accounts.subscription.as_deref_mut().map(|s| &mut **s),
+        count,
+        clock.unix_timestamp,
+    )?;
+    require!(price <= max_price, MscError::PriceAboveMaximum);
+    
+    charge_service_fee(
+        &ctx.accounts.token_program,
+        &ctx.accounts.user_token_account,
+        &ctx.accounts.treasury_token_account,
+        ctx.accounts.user.to_account_info(),
+        price,
+    )?;
+    
+    let sequence = ctx.accounts.global_state.next_receipt()?;
+    let ledger = read_ledger_anchor(clock.slot, &ctx.accounts.slot_hashes)?;
+    let batch_claim = &mut ctx.accounts.batch_claim;
+    batch_claim.owner = ctx.accounts.user.key();
+    batch_claim.merkle_root = merkle_root;
+    batch_claim.count = count;
+    batch_claim.depth = BatchClaim::tree_depth(count);
+    batch_claim.amount_paid = price;
+    batch_claim.timestamp = clock.unix_timestamp;
+    batch_claim.sequence = sequence;
+    batch_claim.slot = ledger.slot;
+    batch_claim.recent_slot = ledger.recent_slot;
+    batch_claim.recent_slot_hash = ledger.recent_slot_hash;
+    batch_claim.is_active = true;
+    
+    msg!("Batch claim created:");
+    msg!("Owner: {}", batch_claim.owner);
+    msg!("Merkle Root: {:?}", merkle_root);
+    msg!("Count: {}", count);
+    msg!("Amount: {} MSC", price);
+    msg!("Receipt: {}", generate_transaction_id(sequence));
+    
+    Ok(())
+}
+
+// 验证文件哈希属于批量确权 (无需权限)
+pub fn verify_batch_membership(
+    ctx: Context<VerifyBatchMembership>,
+    file_hash: String,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let batch_claim = &ctx.accounts.batch_claim;
+    require!(batch_claim.is_active, MscError::ClaimNotFound);
+    require!(validate_file_hash(&file_hash), MscError::InvalidFileHash);
+    // 证明长度不得超过批次树深度，防止以内部节点冒充叶子
+    require!(proof.len() <= batch_claim.depth as usize, MscError::InvalidMerkleProof);
+    
+    let root = compute_merkle_root(&batch_leaf(&file_hash), &proof);
+    require!(root == batch_claim.merkle_root, MscError::InvalidMerkleProof);
+    
+    msg!("Batch membership verified:");
+    msg!("Batch: {}", batch_claim.key());
+    msg!("Owner: {}", batch_claim.owner);
+    msg!("File Hash: {}", file_hash);
+    msg!("Timestamp: {}", batch_claim.timestamp);
+    
+    Ok(())
+}
+
+// Account 结构定义
+
+#[derive(Accounts)]
+#[instruction(merkle_root: [u8; 32])]
+pub struct CreateBatchClaim<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = BatchClaim::LEN,
+        seeds = [b"batch_claim", user.key().as_ref(), merkle_root.as_ref()],
+        bump
+    )]
+    pub batch_claim: Account<'info, BatchClaim>,
+    
+    #[account(
+        seeds = [b"service_config", [SERVICE_CLAIM].as_ref()],
+        bump
+    )]
+    pub service_config: Account<'info, ServiceConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"global_state"],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+    
+    /// CHECK: SlotHashes sysvar，读取最新条目锚定确权
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+    
+    // 可选：以订阅额度抵扣确权费用
+    #[account(
+        mut,
+        seeds = [b"subscription", user.key().as_ref()],
+        bump
+    )]
+    pub subscription: Option<Box<Account<'info, Subscription>>>,
+    
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Box<Account<'info, MscTokenConfig>>,
+    
+    #[account(mut)]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyBatchMembership<'info> {
+    pub batch_claim: Account<'info, BatchClaim>,
+}
+
+// 辅助函数：计算文件哈希对应的叶子节点
+pub fn batch_leaf(file_hash: &str) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, file_hash.as_bytes()]).to_bytes()
+}
+
+// 辅助函数：按排序配对方式由叶子和证明计算 Merkle 根
+pub fn compute_merkle_root(leaf: &[u8; 32], proof: &[[u8; 32]]) -> [u8; 32] {
+    proof.iter().fold(*leaf, |node, sibling| {
+        let (left, right) = if node <= *sibling {
+            (node, *sibling)
+        } else {
+            (*sibling, node)
+        };
+        hashv(&[NODE_PREFIX, &left, &right]).to_bytes()
+    })
+}
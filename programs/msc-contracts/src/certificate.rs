@@ -0,0 +1,172 @@
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::mpl_token_metadata::types::DataV2;
+use anchor_spl::metadata::{create_metadata_accounts_v3, CreateMetadataAccountsV3};
+use anchor_spl::token::spl_token::instruction::AuthorityType;
+use anchor_spl::token::{self, MintTo, SetAuthority, TokenAccount};
+use crate::state::*;
+use crate::error::*;
+use crate::owner_index::transfer_indexed_claim;
+use crate::service::PayAndCreateClaim;
+
+// 确权证书 NFT 元数据
+const CERTIFICATE_NAME: &str = "MSC Claim Certificate";
+const CERTIFICATE_SYMBOL: &str = "MSCC";
+
+// 根据证书 NFT 当前持有人同步确权所有者 (无需权限)
+pub fn sync_claim_owner(
+    ctx: Context<SyncClaimOwner>,
+) -> Result<()> {
+    let certificate_token_account = &ctx.accounts.certificate_token_account;
+    let claim = &mut ctx.accounts.claim;
+    
+    require!(claim.is_active, MscError::ClaimNotFound);
+    // 争议期间冻结所有权，证书持有人不能绕过冻结同步所有者
+    require!(!claim.is_disputed, MscError::ClaimDisputed);
+    require!(
+        claim.certificate_mint == Some(certificate_token_account.mint),
+        MscError::InvalidMint
+    );
+    require!(certificate_token_account.amount == 1, MscError::CertificateNotHeld);
+    
+    let old_owner = claim.owner;
+    if certificate_token_account.owner != old_owner {
+        transfer_indexed_claim(
+            claim,
+            certificate_token_account.owner,
+            ctx.accounts.from_owner_index.as_deref_mut(),
+            Some(&mut ctx.accounts.to_owner_index_head),
+            Some(&mut ctx.accounts.to_owner_index),
+        )?;
+    }
+    
+    msg!("Claim owner synced from certificate:");
+    msg!("Claim: {}", claim.key());
+    msg!("From: {}", old_owner);
+    msg!("To: {}", claim.owner);
+    
+    Ok(())
+}
+
+// Account 结构定义
+
+#[derive(Accounts)]
+pub struct SyncClaimOwner<'info> {
+    #[account(mut)]
+    pub claim: Account<'info, OwnershipClaim>,
+    
+    pub certificate_token_account: Account<'info, TokenAccount>,
+    
+    // 确权已建索引且持有人变更时传入原所有者索引页
+    #[account(mut)]
+    pub from_owner_index: Option<Account<'info, OwnerIndexPage>>,
+    
+    // 证书持有人的索引头与当前索引页 (首次使用时创建)
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = OwnerIndexHead::LEN,
+        seeds = [b"owner_index_head", certificate_token_account.owner.as_ref()],
+        bump
+    )]
+    pub to_owner_index_head: Box<Account<'info, OwnerIndexHead>>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = OwnerIndexPage::LEN,
+        seeds = [b"owner_index", certificate_token_account.owner.as_ref(), to_owner_index_head.current_page.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub to_owner_index: Box<Account<'info, OwnerIndexPage>>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+// 辅助函数：为新建确权铸造 1-of-1 证书 NFT 并撤销铸币权限
+// 未传入证书账户时返回 None
+pub fn mint_claim_certificate<'info>(
+    accounts: &PayAndCreateClaim<'info>,
+    authority_bump: Option<u8>,
+) -> Result<Option<Pubkey>> {
+    let Some(mint) = accounts.certificate_mint.as_ref() else {
+        return Ok(None);
+    };
+    let token_account = accounts
+        .certificate_token_account
+        .as_ref()
+        .ok_or(MscError::CertificateAccountsMissing)?;
+    let metadata = accounts
+        .certificate_metadata
+        .as_ref()
+        .ok_or(MscError::CertificateAccountsMissing)?;
+    let authority = accounts
+        .certificate_authority
+        .as_ref()
+        .ok_or(MscError::CertificateAccountsMissing)?;
+    let metadata_program = accounts
+        .token_metadata_program
+        .as_ref()
+        .ok_or(MscError::CertificateAccountsMissing)?;
+    let rent = accounts.rent.as_ref().ok_or(MscError::CertificateAccountsMissing)?;
+    let bump = authority_bump.ok_or(MscError::CertificateAccountsMissing)?;
+    
+    let seeds: &[&[u8]] = &[b"certificate_authority", &[bump]];
+    let signer = &[seeds];
+    let token_program = accounts.token_program.to_account_info();
+    
+    // 铸造唯一一枚证书
+    let cpi_accounts = MintTo {
+        mint: mint.to_account_info(),
+        to: token_account.to_account_info(),
+        authority: authority.to_account_info(),
+    };
+    token::mint_to(
+        CpiContext::new_with_signer(token_program.clone(), cpi_accounts, signer),
+        1,
+    )?;
+    
+    // 元数据指向确权记录 PDA 与文件哈希
+    let claim = &accounts.claim;
+    let data = DataV2 {
+        name: CERTIFICATE_NAME.to_string(),
+        symbol: CERTIFICATE_SYMBOL.to_string(),
+        uri: format!("solana:{}?file_hash={}", claim.key(), claim.file_hash),
+        seller_fee_basis_points: 0,
+        creators: None,
+        collection: None,
+        uses: None,
+    };
+    let cpi_accounts = CreateMetadataAccountsV3 {
+        metadata: metadata.to_account_info(),
+        mint: mint.to_account_info(),
+        mint_authority: authority.to_account_info(),
+        payer: accounts.user.to_account_info(),
+        update_authority: authority.to_account_info(),
+        system_program: accounts.system_program.to_account_info(),
+        rent: rent.to_account_info(),
+    };
+    create_metadata_accounts_v3(
+        CpiContext::new_with_signer(metadata_program.to_account_info(), cpi_accounts, signer),
+        data,
+        false,
+        true,
+        None,
+    )?;
+    
+    // 撤销铸币权限，保证供应量恒为 1
+    let cpi_accounts = SetAuthority {
+        current_authority: authority.to_account_info(),
+        account_or_mint: mint.to_account_info(),
+    };
+    token::set_authority(
+        CpiContext::new_with_signer(token_program, cpi_accounts, signer),
+        AuthorityType::MintTokens,
+        None,
+    )?;
+    
+    msg!("Claim certificate minted: {}", mint.key());
+    Ok(Some(mint.key()))
+}
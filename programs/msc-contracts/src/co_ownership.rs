@@ -0,0 +1,385 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::owner_index::transfer_indexed_claim;
+
+// 设置确权共有人 (由当前单一所有者发起)
+pub fn set_co_owners(
+    ctx: Context<SetCoOwners>,
+    co_owners: Vec<CoOwnerShare>,
+    approval_threshold: u8,
+) -> Result<()> {
+    let claim = &mut ctx.accounts.claim;
+    require!(claim.is_active, MscError::ClaimNotFound);
+    require!(claim.owner == ctx.accounts.owner.key(), MscError::InvalidAccountOwner);
+    require!(!claim.is_co_owned, MscError::ClaimCoOwned);
+    require!(claim.certificate_mint.is_none(), MscError::ClaimHasCertificate);
+    require!(!claim.is_disputed, MscError::ClaimDisputed);
+    require!(!claim.is_listed, MscError::ClaimListed);
+    
+    validate_co_owner_shares(&co_owners)?;
+    require!(
+        approval_threshold > 0 && approval_threshold as usize <= co_owners.len(),
+        MscError::InvalidApprovalThreshold
+    );
+    
+    let claim_co_owners = &mut ctx.accounts.co_owners;
+    claim_co_owners.claim = claim.key();
+    claim_co_owners.approval_threshold = approval_threshold;
+    claim_co_owners.first_proposal = claim.proposal_count;
+    claim_co_owners.co_owners = co_owners;
+    
+    claim.is_co_owned = true;
+    
+    msg!("Claim co-owners set:");
+    msg!("Claim: {}", claim.key());
+    msg!("Co-owners: {}", claim_co_owners.co_owners.len());
+    msg!("Approval Threshold: {}", approval_threshold);
+    
+    Ok(())
+}
+
+// 转让共有份额 (无需其他共有人批准)
+pub fn transfer_share(
+    ctx: Context<TransferShare>,
+    share_bps: u16,
+    recipient: Pubkey,
+) -> Result<()> {
+    let claim = &ctx.accounts.claim;
+    require!(claim.is_active, MscError::ClaimNotFound);
+    require!(!claim.is_disputed, MscError::ClaimDisputed);
+    require!(!claim.is_listed, MscError::ClaimListed);
+    require!(share_bps > 0, MscError::InsufficientShare);
+    require!(recipient != Pubkey::default(), MscError::InvalidRecipient);
+    
+    let sender = ctx.accounts.co_owner.key();
+    let claim_co_owners = &mut ctx.accounts.co_owners;
+    
+    let sender_index = claim_co_owners
+        .co_owners
+        .iter()
+        .position(|c| c.owner == sender)
+        .ok_or(MscError::NotCoOwner)?;
+    
+    let remaining = claim_co_owners.co_owners[sender_index]
+        .share_bps
+        .checked_sub(share_bps)
+        .ok_or(MscError::InsufficientShare)?;
+    claim_co_owners.co_owners[sender_index].share_bps = remaining;
+    
+    match claim_co_owners.co_owners.iter_mut().find(|c| c.owner == recipient) {
+        Some(entry) => {
+            entry.share_bps = entry.share_bps.checked_add(share_bps).ok_or(MscError::MathOverflow)?;
+        }
+        None => {
+            claim_co_owners.co_owners.push(CoOwnerShare {
+                owner: recipient,
+                share_bps,
+            });
+        }
+    }
+    
+    // 份额清零的共有人退出
+    claim_co_owners.co_owners.retain(|c| c.share_bps > 0);
+    require!(
+        claim_co_owners.co_owners.len() <= ClaimCoOwners::MAX_CO_OWNERS,
+        MscError::TooManyCoOwners
+    );
+    
+    // 共有人减少时阈值不能超过人数
+    let co_owner_count = claim_co_owners.co_owners.len() as u8;
+    if claim_co_owners.approval_threshold > co_owner_count {
+        claim_co_owners.approval_threshold = co_owner_count;
+    }
+    
+    msg!("Co-owner share transferred:");
+    msg!("Claim: {}", claim_co_owners.claim);
+    msg!("From: {}", sender);
+    msg!("To: {}", recipient);
+    msg!("Share: {} bps", share_bps);
+    
+    Ok(())
+}
+
+// 发起共有人提案 (转让或撤销)
+pub fn propose_claim_action(
+    ctx: Context<ProposeClaimAction>,
+    action: ClaimAction,
+) -> Result<()> {
+    require!(ctx.accounts.claim.is_active, MscError::ClaimNotFound);
+    
+    let proposer = ctx.accounts.proposer.key();
+    let claim_co_owners = &ctx.accounts.co_owners;
+    require!(claim_co_owners.is_co_owner(&proposer), MscError::NotCoOwner);
+    
+    if let ClaimAction::Transfer { new_owner } = action {
+        require!(new_owner != Pubkey::default(), MscError::InvalidRecipient);
+    }
+    
+    let clock = Clock::get()?;
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.claim = claim_co_owners.claim;
+    proposal.claim_sequence = ctx.accounts.claim.sequence;
+    proposal.index = ctx.accounts.claim.proposal_count;
+    proposal.proposer = proposer;
+    proposal.action = action;
+    proposal.approvals = vec![proposer]; // 发起人默认批准
+    proposal.executed = false;
+    proposal.created_at = clock.unix_timestamp;
+    
+    // 计数记在确权记录上且 PDA 含确权序号，共有人账户或确权记录重建后提案 PDA 不会重复
+    let claim = &mut ctx.accounts.claim;
+    claim.proposal_count = claim
+        .proposal_count
+        .checked_add(1)
+        .ok_or(MscError::MathOverflow)?;
+    
+    msg!("Co-owner proposal created:");
+    msg!("Claim: {}", proposal.claim);
+    msg!("Proposer: {}", proposer);
+    msg!("Proposal: {}", ctx.accounts.proposal.key());
+    
+    Ok(())
+}
+
+// 批准共有人提案
+pub fn approve_claim_action(
+    ctx: Context<ApproveClaimAction>,
+) -> Result<()> {
+    let approver = ctx.accounts.co_owner.key();
+    require!(ctx.accounts.co_owners.is_co_owner(&approver), MscError::NotCoOwner);
+    
+    let proposal = &mut ctx.accounts.proposal;
+    require!(!proposal.executed, MscError::ProposalAlreadyExecuted);
+    require!(
+        proposal.is_current(&ctx.accounts.claim, &ctx.accounts.co_owners),
+        MscError::StaleProposal
+    );
+    require!(!proposal.approvals.contains(&approver), MscError::ProposalAlreadyApproved);
+    require!(
+        proposal.approvals.len() < ClaimCoOwners::MAX_CO_OWNERS,
+        MscError::TooManyCoOwners
+    );
+    
+    proposal.approvals.push(approver);
+    
+    msg!("Co-owner proposal approved:");
+    msg!("Proposal: {}", proposal.key());
+    msg!("Approver: {}", approver);
+    msg!("Approvals: {}", proposal.approvals.len());
+    
+    Ok(())
+}
+
+// 执行共有人提案 (批准数达到阈值)
+pub fn execute_claim_action(
+    ctx: Context<ExecuteClaimAction>,
+) -> Result<()> {
+    let executor = ctx.accounts.co_owner.key();
+    let claim_co_owners = &ctx.accounts.co_owners;
+    require!(claim_co_owners.is_co_owner(&executor), MscError::NotCoOwner);
+    
+    let proposal = &mut ctx.accounts.proposal;
+    require!(!proposal.executed, MscError::ProposalAlreadyExecuted);
+    require!(
+        proposal.is_current(&ctx.accounts.claim, claim_co_owners),
+        MscError::StaleProposal
+    );
+    
+    // 只统计仍持有份额的共有人的批准
+    let approvals = proposal
+        .approvals
+        .iter()
+        .filter(|approver| claim_co_owners.is_co_owner(approver))
+        .count();
+    require!(
+        approvals >= claim_co_owners.approval_threshold as usize,
+        MscError::InsufficientApprovals
+    );
+    
+    proposal.executed = true;
+    
+    let claim = &mut ctx.accounts.claim;
+    require!(claim.is_active, MscError::ClaimNotFound);
+    require!(!claim.is_disputed, MscError::ClaimDisputed);
+    
+    match proposal.action.clone() {
+        ClaimAction::Transfer { new_owner } => {
+            let clock = Clock::get()?;
+            require!(!claim.is_expired(clock.unix_timestamp), MscError::ClaimExpired);
+            // 转让后恢复为单一所有者，关闭共有人账户
+            transfer_indexed_claim(
+                claim,
+                new_owner,
+                ctx.accounts.from_owner_index.as_deref_mut(),
+                ctx.accounts.to_owner_index_head.as_deref_mut().map(|head| &mut **head),
+                ctx.accounts.to_owner_index.as_deref_mut().map(|page| &mut **page),
+            )?;
+            claim.is_co_owned = false;
+            ctx.accounts.co_owners.close(ctx.accounts.co_owner.to_account_info())?;
+            
+            msg!("Co-owned claim transferred to: {}", new_owner);
+        }
+        ClaimAction::Revoke => {
+            claim.is_active = false;
+            
+            msg!("Co-owned claim revoked");
+        }
+    }
+    
+    msg!("Proposal executed: {}", proposal.key());
+    msg!("Approvals: {}", approvals);
+    
+    Ok(())
+}
+
+// Account 结构定义
+
+#[derive(Accounts)]
+pub struct SetCoOwners<'info> {
+    #[account(mut)]
+    pub claim: Account<'info, OwnershipClaim>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = ClaimCoOwners::LEN,
+        seeds = [b"co_owners", claim.key().as_ref()],
+        bump
+    )]
+    pub co_owners: Account<'info, ClaimCoOwners>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TransferShare<'info> {
+    pub claim: Account<'info, OwnershipClaim>,
+    
+    #[account(
+        mut,
+        seeds = [b"co_owners", claim.key().as_ref()],
+        bump
+    )]
+    pub co_owners: Account<'info, ClaimCoOwners>,
+    
+    pub co_owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeClaimAction<'info> {
+    #[account(mut)]
+    pub claim: Account<'info, OwnershipClaim>,
+    
+    #[account(
+        seeds = [b"co_owners", claim.key().as_ref()],
+        bump
+    )]
+    pub co_owners: Account<'info, ClaimCoOwners>,
+    
+    #[account(
+        init,
+        payer = proposer,
+        space = CoOwnerProposal::LEN,
+        seeds = [
+            b"co_owner_proposal",
+            claim.key().as_ref(),
+            claim.sequence.to_le_bytes().as_ref(),
+            claim.proposal_count.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub proposal: Account<'info, CoOwnerProposal>,
+    
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveClaimAction<'info> {
+    #[account(address = proposal.claim)]
+    pub claim: Account<'info, OwnershipClaim>,
+    
+    #[account(
+        seeds = [b"co_owners", proposal.claim.as_ref()],
+        bump
+    )]
+    pub co_owners: Account<'info, ClaimCoOwners>,
+    
+    #[account(mut)]
+    pub proposal: Account<'info, CoOwnerProposal>,
+    
+    pub co_owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteClaimAction<'info> {
+    #[account(
+        mut,
+        address = proposal.claim
+    )]
+    pub claim: Account<'info, OwnershipClaim>,
+    
+    #[account(
+        mut,
+        seeds = [b"co_owners", claim.key().as_ref()],
+        bump
+    )]
+    pub co_owners: Account<'info, ClaimCoOwners>,
+    
+    #[account(mut)]
+    pub proposal: Account<'info, CoOwnerProposal>,
+    
+    // 执行转让提案且确权已建索引时传入原所有者索引页
+    #[account(mut)]
+    pub from_owner_index: Option<Account<'info, OwnerIndexPage>>,
+    
+    // 执行转让提案时传入受让人的索引头与当前索引页 (首次使用时创建)
+    #[account(
+        init_if_needed,
+        payer = co_owner,
+        space = OwnerIndexHead::LEN,
+        seeds = [b"owner_index_head", proposal.action.recipient().as_ref()],
+        bump
+    )]
+    pub to_owner_index_head: Option<Box<Account<'info, OwnerIndexHead>>>,
+    
+    #[account(
+        init_if_needed,
+        payer = co_owner,
+        space = OwnerIndexPage::LEN,
+        seeds = [b"owner_index", proposal.action.recipient().as_ref(), to_owner_index_head.as_ref().map(|head| head.current_page).unwrap_or_default().to_le_bytes().as_ref()],
+        bump
+    )]
+    pub to_owner_index: Option<Box<Account<'info, OwnerIndexPage>>>,
+    
+    #[account(mut)]
+    pub co_owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+// 辅助函数：验证共有人份额分配
+pub fn validate_co_owner_shares(co_owners: &[CoOwnerShare]) -> Result<()> {
+    require!(!co_owners.is_empty(), MscError::InvalidShareDistribution);
+    require!(co_owners.len() <= ClaimCoOwners::MAX_CO_OWNERS, MscError::TooManyCoOwners);
+    
+    let mut total: u16 = 0;
+    for (i, entry) in co_owners.iter().enumerate() {
+        require!(entry.share_bps > 0, MscError::InvalidShareDistribution);
+        require!(entry.owner != Pubkey::default(), MscError::InvalidRecipient);
+        require!(
+            !co_owners[..i].iter().any(|c| c.owner == entry.owner),
+            MscError::DuplicateCoOwner
+        );
+        total = total.checked_add(entry.share_bps).ok_or(MscError::InvalidShareDistribution)?;
+    }
+    require!(total == ClaimCoOwners::TOTAL_SHARE_BPS, MscError::InvalidShareDistribution);
+    
+    Ok(())
+}
@@ -0,0 +1,406 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::slot_hashes;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::state::*;
+use crate::error::*;
+use crate::ownership::{validate_file_hash, resolve_claim_lineage, read_ledger_anchor, generate_transaction_id, LedgerAnchor};
+use crate::subscription::apply_claim_credits;
+use crate::service::{
+    charge_service_fee, get_service_price, register_new_claim, release_payment_escrow, ClaimRegistration,
+    NewClaim, SERVICE_CLAIM,
+};
+
+// 提交后允许揭示的最大 slot 数 (约 10 分钟)
+pub const CLAIM_REVEAL_WINDOW_SLOTS: u64 = 1_500;
+
+// 提交确权承诺并将费用托管至 escrow，交易中不出现明文文件哈希
+// 确权时间戳与账本锚点均在提交时确定
+pub fn commit_claim(
+    ctx: Context<CommitClaim>,
+    commitment: [u8; 32],
+    max_price: u64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    
+    // 传入订阅账户时在提交阶段扣减订阅额度，揭示时无需再付费
+    let price = apply_claim_credits(
+        get_service_price(&ctx.accounts.service_config, SERVICE_CLAIM)?,
+        ctx.accounts.subscription.as_deref_mut().map(|s| &mut **s),
+        1,
+        clock.unix_timestamp,
+    )?;
+    require!(price <= max_price, MscError::PriceAboveMaximum);
+    
+    if price > 0 {
+        charge_service_fee(
+            &ctx.accounts.token_program,
+            &ctx.accounts.user_token_account,
+            &ctx.accounts.escrow_token_account,
+            ctx.accounts.user.to_account_info(),
+            price,
+        )?;
+    }
+    
+    let ledger = read_ledger_anchor(clock.slot, &ctx.accounts.slot_hashes)?;
+    let payment_sequence = ctx.accounts.global_state.next_receipt()?;
+    
+    let claim_commitment = &mut ctx.accounts.claim_commitment;
+    claim_commitment.owner = ctx.accounts.user.key();
+    claim_commitment.commitment = commitment;
+    claim_commitment.amount_paid = price;
+    claim_commitment.payment_sequence = payment_sequence;
+    claim_commitment.committed_at = clock.unix_timestamp;
+    claim_commitment.committed_slot = ledger.slot;
+    claim_commitment.recent_slot = ledger.recent_slot;
+    claim_commitment.recent_slot_hash = ledger.recent_slot_hash;
+    
+    msg!("Claim commitment recorded:");
+    msg!("User: {}", claim_commitment.owner);
+    msg!("Amount: {} MSC", price);
+    msg!("Receipt: {}", generate_transaction_id(payment_sequence));
+    msg!("Slot: {}", clock.slot);
+    
+    Ok(())
+}
+
+// 揭示文件哈希并创建确权记录，escrow 费用划入金库
+// 确权时间戳与账本锚点沿用提交时的记录
+pub fn reveal_claim(
+    ctx: Context<RevealClaim>,
+    file_hash: String,
+    salt: [u8; 32],
+    relation: ClaimRelation,
+) -> Result<()> {
+    require!(validate_file_hash(&file_hash), MscError::InvalidFileHash);
+    
+    let user = ctx.accounts.user.key();
+    let claim_commitment = &ctx.accounts.claim_commitment;
+    require!(
+        claim_commitment_hash(&file_hash, &user, &salt) == claim_commitment.commitment,
+        MscError::CommitmentMismatch
+    );
+    
+    let clock = Clock::get()?;
+    require!(clock.slot <= reveal_deadline(claim_commitment)?, MscError::RevealWindowExpired);
+    
+    let treasury_token_account = &ctx.accounts.treasury_token_account;
+    require!(
+        treasury_token_account.key() == ctx.accounts.service_config.treasury,
+        MscError::InvalidTreasury
+    );
+    require!(
+        treasury_token_account.mint == ctx.accounts.escrow_token_account.mint,
+        MscError::InvalidMint
+    );
+    
+    let lineage = resolve_claim_lineage(
+        ctx.accounts.parent_claim.as_ref(),
+        relation,
+        &user,
+    )?;
+    
+    let amount_paid = claim_commitment.amount_paid;
+    let committed_at = claim_commitment.committed_at;
+    let payment_sequence = claim_commitment.payment_sequence;
+    let ledger = LedgerAnchor {
+        slot: claim_commitment.committed_slot,
+        recent_slot: claim_commitment.recent_slot,
+        recent_slot_hash: claim_commitment.recent_slot_hash,
+    };
+    
+    release_payment_escrow(
+        &ctx.accounts.token_program,
+        &ctx.accounts.escrow_token_account,
+        &ctx.accounts.treasury_token_account,
+        &ctx.accounts.escrow_authority,
+        ctx.accounts.user.to_account_info(),
+        ctx.bumps.escrow_authority,
+        amount_paid,
+    )?;
+    
+    let claim_sequence = register_new_claim(
+        ClaimRegistration {
+            claim: &mut ctx.accounts.claim,
+            payment_record: &mut ctx.accounts.payment_record,
+            global_state: &mut ctx.accounts.global_state,
+            owner_index_head: &mut ctx.accounts.owner_index_head,
+            owner_index: &mut ctx.accounts.owner_index,
+            stats: &mut **ctx.accounts.user_stats,
+        },
+        NewClaim {
+            owner: user,
+            payer: user,
+            file_hash: &file_hash,
+            lineage: &lineage,
+            amount: amount_paid,
+            certificate_amount: 0,
+            timestamp: committed_at,
+            ledger,
+            payment_sequence: Some(payment_sequence),
+        },
+    )?;
+    
+    msg!("Claim revealed:");
+    msg!("User: {}", user);
+    msg!("File Hash: {}", file_hash);
+    msg!("Receipt: {}", generate_transaction_id(claim_sequence));
+    msg!("Committed At: {}", committed_at);
+    
+    Ok(())
+}
+
+// 关闭超出揭示窗口仍未揭示的承诺，escrow 费用与租金退还提交人 (任何人可调用)
+// 以订阅额度提交的承诺不退还额度
+pub fn expire_commitment(ctx: Context<ExpireCommitment>) -> Result<()> {
+    let claim_commitment = &ctx.accounts.claim_commitment;
+    let clock = Clock::get()?;
+    require!(clock.slot > reveal_deadline(claim_commitment)?, MscError::CommitmentNotExpired);
+    
+    let amount = claim_commitment.amount_paid;
+    release_payment_escrow(
+        &ctx.accounts.token_program,
+        &ctx.accounts.escrow_token_account,
+        &ctx.accounts.owner_token_account,
+        &ctx.accounts.escrow_authority,
+        ctx.accounts.owner.to_account_info(),
+        ctx.bumps.escrow_authority,
+        amount,
+    )?;
+    
+    msg!("Claim commitment expired:");
+    msg!("Owner: {}", claim_commitment.owner);
+    msg!("Refund: {} MSC", amount);
+    msg!("Receipt: {}", generate_transaction_id(claim_commitment.payment_sequence));
+    
+    Ok(())
+}
+
+// 辅助函数：承诺最晚可揭示的 slot
+fn reveal_deadline(claim_commitment: &ClaimCommitment) -> Result<u64> {
+    claim_commitment
+        .committed_slot
+        .checked_add(CLAIM_REVEAL_WINDOW_SLOTS)
+        .ok_or(error!(MscError::MathOverflow))
+}
+
+// 辅助函数：计算确权承诺哈希
+pub fn claim_commitment_hash(file_hash: &str, owner: &Pubkey, salt: &[u8; 32]) -> [u8; 32] {
+    hashv(&[file_hash.as_bytes(), owner.as_ref(), salt]).to_bytes()
+}
+
+// Account 结构定义
+
+#[derive(Accounts)]
+#[instruction(commitment: [u8; 32])]
+pub struct CommitClaim<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = ClaimCommitment::LEN,
+        seeds = [b"claim_commitment", user.key().as_ref(), commitment.as_ref()],
+        bump
+    )]
+    pub claim_commitment: Account<'info, ClaimCommitment>,
+    
+    #[account(
+        seeds = [b"service_config", [SERVICE_CLAIM].as_ref()],
+        bump
+    )]
+    pub service_config: Box<Account<'info, ServiceConfig>>,
+    
+    #[account(
+        mut,
+        seeds = [b"global_state"],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+    
+    // 可选：以订阅额度抵扣确权费用
+    #[account(
+        mut,
+        seeds = [b"subscription", user.key().as_ref()],
+        bump
+    )]
+    pub subscription: Option<Box<Account<'info, Subscription>>>,
+    
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Box<Account<'info, MscTokenConfig>>,
+    
+    #[account(address = config.mint @ MscError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    // 揭示前托管确权费用
+    #[account(
+        init,
+        payer = user,
+        seeds = [b"commitment_escrow", claim_commitment.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = escrow_authority
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: escrow 代币账户的 PDA 权限账户，不存储数据
+    #[account(
+        seeds = [b"escrow_authority"],
+        bump
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = user_token_account.mint == mint.key() @ MscError::InvalidMint,
+        constraint = user_token_account.owner == user.key() @ MscError::InvalidAccountOwner
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: SlotHashes sysvar，提交时读取最新条目锚定确权
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(file_hash: String)]
+pub struct RevealClaim<'info> {
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"claim_commitment", user.key().as_ref(), claim_commitment.commitment.as_ref()],
+        bump,
+        constraint = claim_commitment.owner == user.key() @ MscError::InvalidAuthority
+    )]
+    pub claim_commitment: Account<'info, ClaimCommitment>,
+    
+    #[account(
+        init,
+        payer = user,
+        space = PaymentRecord::LEN,
+        seeds = [b"payment", user.key().as_ref(), file_hash.as_bytes()],
+        bump
+    )]
+    pub payment_record: Box<Account<'info, PaymentRecord>>,
+    
+    #[account(
+        init,
+        payer = user,
+        space = OwnershipClaim::LEN,
+        seeds = [b"claim", user.key().as_ref(), file_hash.as_bytes()],
+        bump
+    )]
+    pub claim: Box<Account<'info, OwnershipClaim>>,
+    
+    #[account(
+        mut,
+        seeds = [b"global_state"],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+    
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserStats::LEN,
+        seeds = [b"user_stats", user.key().as_ref()],
+        bump
+    )]
+    pub user_stats: Box<Account<'info, UserStats>>,
+    
+    // 所有者索引头与当前索引页 (首次使用时创建，无需事先初始化)
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = OwnerIndexHead::LEN,
+        seeds = [b"owner_index_head", user.key().as_ref()],
+        bump
+    )]
+    pub owner_index_head: Box<Account<'info, OwnerIndexHead>>,
+    
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = OwnerIndexPage::LEN,
+        seeds = [b"owner_index", user.key().as_ref(), owner_index_head.current_page.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub owner_index: Box<Account<'info, OwnerIndexPage>>,
+    
+    // 新版本或衍生作品引用的父确权记录 (原创作品不传)
+    pub parent_claim: Option<Account<'info, OwnershipClaim>>,
+    
+    #[account(
+        seeds = [b"service_config", [SERVICE_CLAIM].as_ref()],
+        bump
+    )]
+    pub service_config: Box<Account<'info, ServiceConfig>>,
+    
+    #[account(
+        mut,
+        seeds = [b"commitment_escrow", claim_commitment.key().as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+    
+    /// CHECK: escrow 代币账户的 PDA 权限账户，不存储数据
+    #[account(
+        seeds = [b"escrow_authority"],
+        bump
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub treasury_token_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireCommitment<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"claim_commitment", owner.key().as_ref(), claim_commitment.commitment.as_ref()],
+        bump
+    )]
+    pub claim_commitment: Account<'info, ClaimCommitment>,
+    
+    #[account(
+        mut,
+        seeds = [b"commitment_escrow", claim_commitment.key().as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: escrow 代币账户的 PDA 权限账户，不存储数据
+    #[account(
+        seeds = [b"escrow_authority"],
+        bump
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = owner_token_account.mint == escrow_token_account.mint @ MscError::InvalidMint,
+        constraint = owner_token_account.owner == owner.key() @ MscError::InvalidRecipient
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: 承诺提交人，接收退款与租金
+    #[account(mut, address = claim_commitment.owner @ MscError::InvalidRecipient)]
+    pub owner: UncheckedAccount<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
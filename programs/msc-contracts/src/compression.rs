@@ -0,0 +1,414 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::keccak;
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use anchor_lang::solana_program::pubkey;
+use anchor_lang::solana_program::sysvar::slot_hashes;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::state::*;
+use crate::error::*;
+use crate::ownership::{generate_transaction_id, read_ledger_anchor, validate_file_hash};
+use crate::subscription::apply_claim_credits;
+use crate::service::{charge_service_fee, get_service_price, validate_payment_accounts, SERVICE_CLAIM};
+
+// SPL 账户压缩程序与 noop 程序
+pub const SPL_ACCOUNT_COMPRESSION_ID: Pubkey = pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+pub const SPL_NOOP_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+// 创建压缩确权树 (仅限管理员)
+// merkle_tree 账户需由客户端预先分配，owner 为账户压缩程序
+pub fn create_claim_tree(
+    ctx: Context<CreateClaimTree>,
+    max_depth: u32,
+    max_buffer_size: u32,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    require!(config.is_initialized, MscError::AccountNotInitialized);
+    require!(ctx.accounts.authority.key() == config.authority, MscError::InvalidAuthority);
+    
+    let claim_tree = &mut ctx.accounts.claim_tree;
+    claim_tree.merkle_tree = ctx.accounts.merkle_tree.key();
+    claim_tree.creator = ctx.accounts.authority.key();
+    claim_tree.max_depth = max_depth;
+    claim_tree.max_buffer_size = max_buffer_size;
+    claim_tree.num_claims = 0;
+    claim_tree.bump = ctx.bumps.claim_tree;
+    
+    let mut args = Vec::with_capacity(8);
+    args.extend_from_slice(&max_depth.to_le_bytes());
+    args.extend_from_slice(&max_buffer_size.to_le_bytes());
+    invoke_compression(
+        "init_empty_merkle_tree",
+        &ctx.accounts.claim_tree,
+        &ctx.accounts.merkle_tree,
+        &ctx.accounts.noop_program,
+        &ctx.accounts.compression_program,
+        &[],
+        args,
+    )?;
+    
+    msg!("Claim tree created:");
+    msg!("Merkle Tree: {}", ctx.accounts.merkle_tree.key());
+    msg!("Max Depth: {}", max_depth);
+    msg!("Max Buffer Size: {}", max_buffer_size);
+    
+    Ok(())
+}
+
+// 支付并创建压缩确权记录
+pub fn pay_and_create_compressed_claim(
+    ctx: Context<PayAndCreateCompressedClaim>,
+    max_price: u64,
+    file_hash: String,
+) -> Result<()> {
+    require!(validate_file_hash(&file_hash), MscError::InvalidFileHash);
+    validate_payment_accounts(
+        &ctx.accounts.config,
+        &ctx.accounts.service_config,
+        &ctx.accounts.user_token_account,
+        &ctx.accounts.treasury_token_account,
+        ctx.accounts.user.key(),
+    )?;
+    let clock = Clock::get()?;
+    
+    // 传入订阅账户时扣减订阅额度，不再收取确权费用
+    let price = apply_claim_credits(
+        get_service_price(&ctx.accounts.service_config, SERVICE_CLAIM)?,
+        ctx.accounts.subscription.as_deref_mut().map(|s| &mut **s),
+        1,
+        clock.unix_timestamp,
+    )?;
+    require!(price <= max_price, MscError::PriceAboveMaximum);
+    
+    charge_service_fee(
+        &ctx.accounts.token_program,
+        &ctx.accounts.user_token_account,
+        &ctx.accounts.treasury_token_account,
+        ctx.accounts.user.to_account_info(),
+        price,
+    )?;
+    
+    let leaf_index = u32::try_from(ctx.accounts.claim_tree.num_claims)
+        .map_err(|_| MscError::MathOverflow)?;
+    let sequence = ctx.accounts.global_state.next_receipt()?;
+    let ledger = read_ledger_anchor(clock.slot, &ctx.accounts.slot_hashes)?;
+    let claim = CompressedClaim {
+        owner: ctx.accounts.user.key(),
+        file_hash,
+        timestamp: clock.unix_timestamp,
+        leaf_index,
+        sequence,
+        slot: ledger.slot,
+        recent_slot: ledger.recent_slot,
+        recent_slot_hash: ledger.recent_slot_hash,
+        is_active: true,
+    };
+    
+    emit_compressed_claim_event(
+        &ctx.accounts.noop_program,
+        ctx.accounts.merkle_tree.key(),
+        CompressedClaimAction::Created,
+        &claim,
+    )?;
+    
+    invoke_compression(
+        "append",
+        &ctx.accounts.claim_tree,
+        &ctx.accounts.merkle_tree,
+        &ctx.accounts.noop_program,
+        &ctx.accounts.compression_program,
+        &[],
+        compressed_claim_leaf(&claim).to_vec(),
+    )?;
+    
+    let claim_tree = &mut ctx.accounts.claim_tree;
+    claim_tree.num_claims = claim_tree.num_claims.checked_add(1).ok_or(MscError::MathOverflow)?;
+    
+    msg!("Compressed claim created:");
+    msg!("User: {}", claim.owner);
+    msg!("Amount: {} MSC", price);
+    msg!("File Hash: {}", claim.file_hash);
+    msg!("Leaf Index: {}", leaf_index);
+    msg!("Receipt: {}", generate_transaction_id(sequence));
+    
+    Ok(())
+}
+
+// 转让压缩确权记录 (证明节点通过 remaining_accounts 传入)
+pub fn transfer_compressed_claim<'info>(
+    ctx: Context<'_, '_, '_, 'info, UpdateCompressedClaim<'info>>,
+    root: [u8; 32],
+    claim: CompressedClaim,
+    new_owner: Pubkey,
+) -> Result<()> {
+    require!(claim.is_active, MscError::ClaimNotFound);
+    require!(claim.owner == ctx.accounts.owner.key(), MscError::InvalidAccountOwner);
+    require!(new_owner != Pubkey::default(), MscError::InvalidRecipient);
+    
+    let updated = CompressedClaim {
+        owner: new_owner,
+        ..claim.clone()
+    };
+    replace_compressed_claim(&ctx, root, &claim, &updated, CompressedClaimAction::Transferred)?;
+    
+    msg!("Compressed claim transferred:");
+    msg!("File Hash: {}", claim.file_hash);
+    msg!("From: {}", claim.owner);
+    msg!("To: {}", new_owner);
+    
+    Ok(())
+}
+
+// 撤销压缩确权记录
+pub fn revoke_compressed_claim<'info>(
+    ctx: Context<'_, '_, '_, 'info, UpdateCompressedClaim<'info>>,
+    root: [u8; 32],
+    claim: CompressedClaim,
+) -> Result<()> {
+    require!(claim.is_active, MscError::ClaimNotFound);
+    require!(claim.owner == ctx.accounts.owner.key(), MscError::InvalidAccountOwner);
+    
+    let updated = CompressedClaim {
+        is_active: false,
+        ..claim.clone()
+    };
+    replace_compressed_claim(&ctx, root, &claim, &updated, CompressedClaimAction::Revoked)?;
+    
+    msg!("Compressed claim revoked:");
+    msg!("Owner: {}", claim.owner);
+    msg!("File Hash: {}", claim.file_hash);
+    
+    Ok(())
+}
+
+// Account 结构定义
+
+#[derive(Accounts)]
+pub struct CreateClaimTree<'info> {
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Account<'info, MscTokenConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = ClaimTree::LEN,
+        seeds = [b"claim_tree", merkle_tree.key().as_ref()],
+        bump
+    )]
+    pub claim_tree: Account<'info, ClaimTree>,
+    
+    /// CHECK: Pre-allocated concurrent Merkle tree, initialized by the account compression program
+    #[account(mut, owner = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: SPL account compression program
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+    
+    /// CHECK: SPL noop program
+    #[account(address = SPL_NOOP_ID)]
+    pub noop_program: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PayAndCreateCompressedClaim<'info> {
+    #[account(
+        mut,
+        seeds = [b"claim_tree", merkle_tree.key().as_ref()],
+        bump = claim_tree.bump
+    )]
+    pub claim_tree: Account<'info, ClaimTree>,
+    
+    /// CHECK: Concurrent Merkle tree owned by the account compression program
+    #[account(mut, owner = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"service_config", [SERVICE_CLAIM].as_ref()],
+        bump
+    )]
+    pub service_config: Account<'info, ServiceConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"global_state"],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+    
+    /// CHECK: SlotHashes sysvar，读取最新条目锚定确权
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+    
+    // 可选：以订阅额度抵扣确权费用
+    #[account(
+        mut,
+        seeds = [b"subscription", user.key().as_ref()],
+        bump
+    )]
+    pub subscription: Option<Box<Account<'info, Subscription>>>,
+    
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Box<Account<'info, MscTokenConfig>>,
+    
+    #[account(mut)]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    /// CHECK: SPL account compression program
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+    
+    /// CHECK: SPL noop program
+    #[account(address = SPL_NOOP_ID)]
+    pub noop_program: UncheckedAccount<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCompressedClaim<'info> {
+    #[account(
+        seeds = [b"claim_tree", merkle_tree.key().as_ref()],
+        bump = claim_tree.bump
+    )]
+    pub claim_tree: Account<'info, ClaimTree>,
+    
+    /// CHECK: Concurrent Merkle tree owned by the account compression program
+    #[account(mut, owner = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    
+    pub owner: Signer<'info>,
+    
+    /// CHECK: SPL account compression program
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+    
+    /// CHECK: SPL noop program
+    #[account(address = SPL_NOOP_ID)]
+    pub noop_program: UncheckedAccount<'info>,
+}
+
+// 辅助函数：计算压缩确权叶子哈希
+pub fn compressed_claim_leaf(claim: &CompressedClaim) -> [u8; 32] {
+    keccak::hashv(&[
+        claim.owner.as_ref(),
+        claim.file_hash.as_bytes(),
+        &claim.timestamp.to_le_bytes(),
+        &claim.leaf_index.to_le_bytes(),
+        &claim.sequence.to_le_bytes(),
+        &claim.slot.to_le_bytes(),
+        &claim.recent_slot.to_le_bytes(),
+        &claim.recent_slot_hash,
+        &[claim.is_active as u8],
+    ])
+    .to_bytes()
+}
+
+// 辅助函数：替换叶子并记录事件
+fn replace_compressed_claim<'info>(
+    ctx: &Context<'_, '_, '_, 'info, UpdateCompressedClaim<'info>>,
+    root: [u8; 32],
+    previous: &CompressedClaim,
+    updated: &CompressedClaim,
+    action: CompressedClaimAction,
+) -> Result<()> {
+    emit_compressed_claim_event(
+        &ctx.accounts.noop_program,
+        ctx.accounts.merkle_tree.key(),
+        action,
+        updated,
+    )?;
+    
+    let mut args = Vec::with_capacity(32 * 3 + 4);
+    args.extend_from_slice(&root);
+    args.extend_from_slice(&compressed_claim_leaf(previous));
+    args.extend_from_slice(&compressed_claim_leaf(updated));
+    args.extend_from_slice(&previous.leaf_index.to_le_bytes());
+    
+    invoke_compression(
+        "replace_leaf",
+        &ctx.accounts.claim_tree,
+        &ctx.accounts.merkle_tree,
+        &ctx.accounts.noop_program,
+        &ctx.accounts.compression_program,
+        ctx.remaining_accounts,
+        args,
+    )
+}
+
+// 辅助函数：通过 noop 程序记录完整叶子数据
+fn emit_compressed_claim_event<'info>(
+    noop_program: &UncheckedAccount<'info>,
+    merkle_tree: Pubkey,
+    action: CompressedClaimAction,
+    claim: &CompressedClaim,
+) -> Result<()> {
+    let event = CompressedClaimEvent {
+        merkle_tree,
+        action,
+        claim: claim.clone(),
+    };
+    let ix = Instruction {
+        program_id: SPL_NOOP_ID,
+        accounts: vec![],
+        data: event.try_to_vec()?,
+    };
+    invoke(&ix, &[noop_program.to_account_info()])?;
+    Ok(())
+}
+
+// 辅助函数：以 ClaimTree PDA 签名调用账户压缩程序
+fn invoke_compression<'info>(
+    instruction_name: &str,
+    claim_tree: &Account<'info, ClaimTree>,
+    merkle_tree: &UncheckedAccount<'info>,
+    noop_program: &UncheckedAccount<'info>,
+    compression_program: &UncheckedAccount<'info>,
+    proof: &[AccountInfo<'info>],
+    args: Vec<u8>,
+) -> Result<()> {
+    let mut data = hash(format!("global:{}", instruction_name).as_bytes()).to_bytes()[..8].to_vec();
+    data.extend_from_slice(&args);
+    
+    let mut accounts = vec![
+        AccountMeta::new(merkle_tree.key(), false),
+        AccountMeta::new_readonly(claim_tree.key(), true),
+        AccountMeta::new_readonly(SPL_NOOP_ID, false),
+    ];
+    accounts.extend(proof.iter().map(|node| AccountMeta::new_readonly(node.key(), false)));
+    
+    let mut account_infos = vec![
+        merkle_tree.to_account_info(),
+        claim_tree.to_account_info(),
+        noop_program.to_account_info(),
+    ];
+    account_infos.extend(proof.iter().cloned());
+    account_infos.push(compression_program.to_account_info());
+    
+    let ix = Instruction {
+        program_id: SPL_ACCOUNT_COMPRESSION_ID,
+        accounts,
+        data,
+    };
+    let seeds: &[&[u8]] = &[b"claim_tree", claim_tree.merkle_tree.as_ref(), &[claim_tree.bump]];
+    invoke_signed(&ix, &account_infos, &[seeds])?;
+    Ok(())
+}
@@ -0,0 +1,383 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::state::*;
+use crate::error::*;
+use crate::owner_index::transfer_indexed_claim;
+use crate::service::{
+    charge_service_fee, get_service_price, release_payment_escrow, SERVICE_DISPUTE_FILING,
+};
+
+// 添加仲裁员 (仅限管理员)
+pub fn add_arbiter(
+    ctx: Context<AddArbiter>,
+    arbiter: Pubkey,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    require!(config.is_initialized, MscError::AccountNotInitialized);
+    require!(ctx.accounts.authority.key() == config.authority, MscError::InvalidAuthority);
+    
+    let clock = Clock::get()?;
+    let arbiter_entry = &mut ctx.accounts.arbiter_entry;
+    arbiter_entry.arbiter = arbiter;
+    arbiter_entry.added_by = ctx.accounts.authority.key();
+    arbiter_entry.added_at = clock.unix_timestamp;
+    arbiter_entry.disputes_resolved = 0;
+    
+    msg!("Arbiter added: {}", arbiter);
+    Ok(())
+}
+
+// 移除仲裁员 (仅限管理员)
+pub fn remove_arbiter(
+    ctx: Context<RemoveArbiter>,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    require!(config.is_initialized, MscError::AccountNotInitialized);
+    require!(ctx.accounts.authority.key() == config.authority, MscError::InvalidAuthority);
+    
+    msg!("Arbiter removed: {}", ctx.accounts.arbiter_entry.arbiter);
+    Ok(())
+}
+
+// 对确权记录发起争议，按争议服务价格缴纳保证金并托管到 escrow
+pub fn open_dispute(
+    ctx: Context<OpenDispute>,
+    evidence_uri: String,
+    evidence_hash: [u8; 32],
+    max_bond: u64,
+) -> Result<()> {
+    require!(
+        evidence_uri.len() <= Dispute::MAX_EVIDENCE_URI_LEN,
+        MscError::MetadataFieldTooLong
+    );
+    
+    let challenger = ctx.accounts.challenger.key();
+    let claim = &ctx.accounts.claim;
+    require!(claim.is_active, MscError::ClaimNotFound);
+    require!(!claim.is_disputed, MscError::ClaimDisputed);
+    require!(claim.owner != challenger, MscError::SelfDispute);
+    
+    let bond = get_service_price(&ctx.accounts.service_config, SERVICE_DISPUTE_FILING)?;
+    require!(bond <= max_bond, MscError::PriceAboveMaximum);
+    
+    charge_service_fee(
+        &ctx.accounts.token_program,
+        &ctx.accounts.challenger_token_account,
+        &ctx.accounts.escrow_token_account,
+        ctx.accounts.challenger.to_account_info(),
+        bond,
+    )?;
+    
+    let clock = Clock::get()?;
+    let dispute = &mut ctx.accounts.dispute;
+    dispute.claim = claim.key();
+    dispute.challenger = challenger;
+    dispute.bond = bond;
+    dispute.evidence_uri = evidence_uri;
+    dispute.evidence_hash = evidence_hash;
+    dispute.status = DisputeStatus::Open;
+    dispute.opened_at = clock.unix_timestamp;
+    dispute.resolved_by = None;
+    dispute.resolved_at = 0;
+    
+    ctx.accounts.claim.is_disputed = true;
+    
+    msg!("Dispute opened:");
+    msg!("Claim: {}", dispute.claim);
+    msg!("Challenger: {}", challenger);
+    msg!("Bond: {} MSC", bond);
+    msg!("Evidence: {}", dispute.evidence_uri);
+    
+    Ok(())
+}
+
+// 仲裁员裁决争议：维持则保证金划入金库，转让或撤销则保证金退还申请人
+pub fn resolve_dispute(
+    ctx: Context<ResolveDispute>,
+    outcome: DisputeOutcome,
+) -> Result<()> {
+    require!(ctx.accounts.dispute.status == DisputeStatus::Open, MscError::DisputeNotOpen);
+    
+    let claim = &ctx.accounts.claim;
+    if outcome == DisputeOutcome::Transfer {
+        require!(claim.certificate_mint.is_none(), MscError::ClaimHasCertificate);
+        require!(!claim.is_co_owned, MscError::ClaimCoOwned);
+    }
+    
+    let bond = ctx.accounts.dispute.bond;
+    let bond_destination = if outcome == DisputeOutcome::Uphold {
+        let treasury_token_account = &ctx.accounts.treasury_token_account;
+        require!(
+            treasury_token_account.key() == ctx.accounts.service_config.treasury,
+            MscError::InvalidTreasury
+        );
+        require!(
+            treasury_token_account.mint == ctx.accounts.escrow_token_account.mint,
+            MscError::InvalidMint
+        );
+        treasury_token_account
+    } else {
+        &ctx.accounts.challenger_token_account
+    };
+    
+    release_payment_escrow(
+        &ctx.accounts.token_program,
+        &ctx.accounts.escrow_token_account,
+        bond_destination,
+        &ctx.accounts.escrow_authority,
+        ctx.accounts.challenger.to_account_info(),
+        ctx.bumps.escrow_authority,
+        bond,
+    )?;
+    
+    // 裁决为 Transfer/Revoke 时关闭挂牌，租金退还卖家，新所有者可重新挂牌
+    if outcome != DisputeOutcome::Uphold && ctx.accounts.claim.is_listed {
+        let listing = ctx.accounts.listing.as_ref().ok_or(MscError::ListingRequired)?;
+        let seller = ctx.accounts.seller.as_ref().ok_or(MscError::ListingRequired)?;
+        require!(seller.key() == listing.seller, MscError::InvalidRecipient);
+        listing.close(seller.to_account_info())?;
+    }
+    
+    let challenger = ctx.accounts.dispute.challenger;
+    let claim = &mut ctx.accounts.claim;
+    claim.is_disputed = false;
+    let status = match outcome {
+        DisputeOutcome::Uphold => DisputeStatus::Upheld,
+        DisputeOutcome::Transfer => {
+            claim.is_listed = false;
+            transfer_indexed_claim(
+                claim,
+                challenger,
+                ctx.accounts.from_owner_index.as_deref_mut(),
+                ctx.accounts.to_owner_index_head.as_deref_mut().map(|head| &mut **head),
+                ctx.accounts.to_owner_index.as_deref_mut().map(|page| &mut **page),
+            )?;
+            DisputeStatus::Transferred
+        }
+        DisputeOutcome::Revoke => {
+            claim.is_active = false;
+            claim.is_listed = false;
+            DisputeStatus::Revoked
+        }
+    };
+    
+    let clock = Clock::get()?;
+    let dispute = &mut ctx.accounts.dispute;
+    dispute.status = status;
+    dispute.resolved_by = Some(ctx.accounts.arbiter.key());
+    dispute.resolved_at = clock.unix_timestamp;
+    
+    let arbiter_entry = &mut ctx.accounts.arbiter_entry;
+    arbiter_entry.disputes_resolved = arbiter_entry
+        .disputes_resolved
+        .checked_add(1)
+        .ok_or(MscError::MathOverflow)?;
+    
+    msg!("Dispute resolved:");
+    msg!("Claim: {}", dispute.claim);
+    msg!("Arbiter: {}", ctx.accounts.arbiter.key());
+    msg!("Bond: {} MSC", bond);
+    msg!("Owner: {}", ctx.accounts.claim.owner);
+    
+    Ok(())
+}
+
+// Account 结构定义
+
+#[derive(Accounts)]
+#[instruction(arbiter: Pubkey)]
+pub struct AddArbiter<'info> {
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Account<'info, MscTokenConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = Arbiter::LEN,
+        seeds = [b"arbiter", arbiter.as_ref()],
+        bump
+    )]
+    pub arbiter_entry: Account<'info, Arbiter>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveArbiter<'info> {
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Account<'info, MscTokenConfig>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"arbiter", arbiter_entry.arbiter.as_ref()],
+        bump
+    )]
+    pub arbiter_entry: Account<'info, Arbiter>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct OpenDispute<'info> {
+    #[account(
+        init,
+        payer = challenger,
+        space = Dispute::LEN,
+        seeds = [b"dispute", claim.key().as_ref(), challenger.key().as_ref()],
+        bump
+    )]
+    pub dispute: Box<Account<'info, Dispute>>,
+    
+    #[account(mut)]
+    pub claim: Box<Account<'info, OwnershipClaim>>,
+    
+    #[account(
+        seeds = [b"service_config", [SERVICE_DISPUTE_FILING].as_ref()],
+        bump
+    )]
+    pub service_config: Box<Account<'info, ServiceConfig>>,
+    
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Box<Account<'info, MscTokenConfig>>,
+    
+    #[account(
+        init,
+        payer = challenger,
+        seeds = [b"dispute_escrow", dispute.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = escrow_authority
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: escrow 代币账户的 PDA 权限账户，不存储数据
+    #[account(
+        seeds = [b"escrow_authority"],
+        bump
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+    
+    #[account(address = config.mint @ MscError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        constraint = challenger_token_account.mint == mint.key() @ MscError::InvalidMint,
+        constraint = challenger_token_account.owner == challenger.key() @ MscError::InvalidAccountOwner
+    )]
+    pub challenger_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub challenger: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    #[account(mut)]
+    pub dispute: Box<Account<'info, Dispute>>,
+    
+    #[account(
+        mut,
+        address = dispute.claim
+    )]
+    pub claim: Box<Account<'info, OwnershipClaim>>,
+    
+    #[account(
+        mut,
+        seeds = [b"arbiter", arbiter.key().as_ref()],
+        bump
+    )]
+    pub arbiter_entry: Account<'info, Arbiter>,
+    
+    #[account(
+        seeds = [b"service_config", [SERVICE_DISPUTE_FILING].as_ref()],
+        bump
+    )]
+    pub service_config: Box<Account<'info, ServiceConfig>>,
+    
+    #[account(
+        mut,
+        seeds = [b"dispute_escrow", dispute.key().as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: escrow 代币账户的 PDA 权限账户，不存储数据
+    #[account(
+        seeds = [b"escrow_authority"],
+        bump
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = challenger_token_account.mint == escrow_token_account.mint @ MscError::InvalidMint,
+        constraint = challenger_token_account.owner == dispute.challenger @ MscError::InvalidRecipient
+    )]
+    pub challenger_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: 仅接收 escrow 账户关闭后返还的租金
+    #[account(mut, address = dispute.challenger)]
+    pub challenger: UncheckedAccount<'info>,
+    
+    // 确权挂牌中且裁决为 Transfer/Revoke 时传入挂牌账户与卖家 (接收租金)
+    #[account(
+        mut,
+        seeds = [b"listing", claim.key().as_ref()],
+        bump
+    )]
+    pub listing: Option<Box<Account<'info, Listing>>>,
+    
+    /// CHECK: 挂牌租金退还给卖家，处理函数中校验为 listing.seller
+    #[account(mut)]
+    pub seller: Option<UncheckedAccount<'info>>,
+    
+    // 裁决为 Transfer 且确权已建索引时传入原所有者索引页
+    #[account(mut)]
+    pub from_owner_index: Option<Account<'info, OwnerIndexPage>>,
+    
+    // 裁决为 Transfer 时传入争议发起人的索引头与当前索引页 (首次使用时创建)
+    #[account(
+        init_if_needed,
+        payer = arbiter,
+        space = OwnerIndexHead::LEN,
+        seeds = [b"owner_index_head", dispute.challenger.as_ref()],
+        bump
+    )]
+    pub to_owner_index_head: Option<Box<Account<'info, OwnerIndexHead>>>,
+    
+    #[account(
+        init_if_needed,
+        payer = arbiter,
+        space = OwnerIndexPage::LEN,
+        seeds = [b"owner_index", dispute.challenger.as_ref(), to_owner_index_head.as_ref().map(|head| head.current_page).unwrap_or_default().to_le_bytes().as_ref()],
+        bump
+    )]
+    pub to_owner_index: Option<Box<Account<'info, OwnerIndexPage>>>,
+    
+    #[account(mut)]
+    pub arbiter: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
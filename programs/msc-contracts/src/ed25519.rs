@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+use crate::error::*;
+
+// Ed25519 预编译指令数据布局
+// [0] 签名数量, [1] 填充, [2..16] 签名偏移结构, 之后为公钥/签名/消息
+const SIGNATURE_OFFSETS_START: usize = 2;
+const SIGNATURE_OFFSETS_LEN: usize = 14;
+const PUBKEY_LEN: usize = 32;
+const SIGNATURE_LEN: usize = 64;
+// 数据位于预编译指令自身时的指令索引
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+// 验证紧邻当前指令之前的 Ed25519 预编译指令：
+// 仅包含一个签名，签名者为 expected_signer，签名消息为 expected_message
+pub fn verify_preceding_ed25519(
+    instructions_sysvar: &AccountInfo,
+    expected_signer: &Pubkey,
+    expected_message: &[u8],
+) -> Result<()> {
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    require!(current_index > 0, MscError::InvalidSignature);
+    
+    let ix = load_instruction_at_checked((current_index - 1) as usize, instructions_sysvar)?;
+    require!(ix.program_id == ed25519_program::ID, MscError::InvalidSignature);
+    require!(ix.accounts.is_empty(), MscError::InvalidSignature);
+    
+    let data = &ix.data;
+    require!(
+        data.len() >= SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_LEN && data[0] == 1,
+        MscError::InvalidSignature
+    );
+    
+    let read_u16 = |offset: usize| -> u16 {
+        let start = SIGNATURE_OFFSETS_START + offset;
+        u16::from_le_bytes([data[start], data[start + 1]])
+    };
+    let signature_offset = read_u16(0) as usize;
+    let signature_instruction_index = read_u16(2);
+    let public_key_offset = read_u16(4) as usize;
+    let public_key_instruction_index = read_u16(6);
+    let message_data_offset = read_u16(8) as usize;
+    let message_data_size = read_u16(10) as usize;
+    let message_instruction_index = read_u16(12);
+    
+    // 公钥、签名、消息必须都位于预编译指令自身的数据中
+    require!(
+        signature_instruction_index == CURRENT_INSTRUCTION
+            && public_key_instruction_index == CURRENT_INSTRUCTION
+            && message_instruction_index == CURRENT_INSTRUCTION,
+        MscError::InvalidSignature
+    );
+    require!(
+        data.len() >= signature_offset + SIGNATURE_LEN,
+        MscError::InvalidSignature
+    );
+    
+    let public_key = data
+        .get(public_key_offset..public_key_offset + PUBKEY_LEN)
+        .ok_or(MscError::InvalidSignature)?;
+    require!(public_key == expected_signer.as_ref(), MscError::InvalidSignature);
+    
+    let message = data
+        .get(message_data_offset..message_data_offset + message_data_size)
+        .ok_or(MscError::InvalidSignature)?;
+    require!(message == expected_message, MscError::InvalidSignature);
+    
+    Ok(())
+}
@@ -88,4 +88,184 @@ pub enum MscError {
     
     #[msg("Airdrop limit exceeded")]
     AirdropLimitExceeded,
+    
+    #[msg("Metadata field too long")]
+    MetadataFieldTooLong,
+    
+    #[msg("Invalid content type")]
+    InvalidContentType,
+    
+    #[msg("Too many co-owners")]
+    TooManyCoOwners,
+    
+    #[msg("Co-owner shares must sum to 10000 basis points")]
+    InvalidShareDistribution,
+    
+    #[msg("Duplicate co-owner")]
+    DuplicateCoOwner,
+    
+    #[msg("Invalid approval threshold")]
+    InvalidApprovalThreshold,
+    
+    #[msg("Signer is not a co-owner")]
+    NotCoOwner,
+    
+    #[msg("Insufficient share")]
+    InsufficientShare,
+    
+    #[msg("Claim is co-owned")]
+    ClaimCoOwned,
+    
+    #[msg("Proposal already approved by signer")]
+    ProposalAlreadyApproved,
+    
+    #[msg("Proposal already executed")]
+    ProposalAlreadyExecuted,
+    
+    #[msg("Not enough co-owner approvals")]
+    InsufficientApprovals,
+    
+    #[msg("Parent claim required for revisions and derivatives")]
+    ParentClaimRequired,
+    
+    #[msg("Original claims cannot reference a parent")]
+    UnexpectedParentClaim,
+    
+    #[msg("Only the parent claim owner can register a revision")]
+    RevisionNotByOwner,
+    
+    #[msg("Invalid ed25519 signature instruction")]
+    InvalidSignature,
+    
+    #[msg("Notary attestation outside the allowed time window")]
+    AttestationExpired,
+    
+    #[msg("Invalid batch size")]
+    InvalidBatchSize,
+    
+    #[msg("Invalid Merkle proof")]
+    InvalidMerkleProof,
+    
+    #[msg("File hash does not match claim")]
+    FileHashMismatch,
+    
+    #[msg("Certificate accounts missing")]
+    CertificateAccountsMissing,
+    
+    #[msg("Claim ownership is tracked by its certificate NFT")]
+    ClaimHasCertificate,
+    
+    #[msg("Certificate not held by token account")]
+    CertificateNotHeld,
+    
+    #[msg("Service is disabled")]
+    ServiceDisabled,
+    
+    #[msg("Service name too long")]
+    ServiceNameTooLong,
+    
+    #[msg("Service price exceeds max_price")]
+    PriceAboveMaximum,
+    
+    #[msg("Payment is not pending")]
+    PaymentNotPending,
+    
+    #[msg("Refund timeout has not elapsed")]
+    RefundNotAvailable,
+    
+    #[msg("Treasury account does not match service configuration")]
+    InvalidTreasury,
+    
+    #[msg("Revealed claim does not match commitment")]
+    CommitmentMismatch,
+    
+    #[msg("Claim reveal window has expired")]
+    RevealWindowExpired,
+    
+    #[msg("Claim has expired")]
+    ClaimExpired,
+    
+    #[msg("Claim grace period has ended")]
+    GracePeriodEnded,
+    
+    #[msg("Claim has not passed its grace period")]
+    ClaimNotExpired,
+    
+    #[msg("Claim is under dispute")]
+    ClaimDisputed,
+    
+    #[msg("Dispute is not open")]
+    DisputeNotOpen,
+    
+    #[msg("Claim owner cannot dispute own claim")]
+    SelfDispute,
+    
+    #[msg("License offer sold out")]
+    LicenseSoldOut,
+    
+    #[msg("License is not transferable")]
+    LicenseNotTransferable,
+    
+    #[msg("License holder mismatch")]
+    LicenseHolderMismatch,
+    
+    #[msg("Claim is listed for sale")]
+    ClaimListed,
+    
+    #[msg("Listing has expired")]
+    ListingExpired,
+    
+    #[msg("Buyer is not allowed for this listing")]
+    BuyerNotAllowed,
+    
+    #[msg("Organization name too long")]
+    OrganizationNameTooLong,
+    
+    #[msg("Too many organization admins")]
+    TooManyOrgAdmins,
+    
+    #[msg("Organization must keep at least one admin")]
+    LastOrgAdmin,
+    
+    #[msg("Signer is not an organization member")]
+    NotOrgMember,
+    
+    #[msg("Owner index page is full")]
+    OwnerIndexPageFull,
+    
+    #[msg("Owner index page required")]
+    OwnerIndexRequired,
+    
+    #[msg("Owner index page does not match claim")]
+    OwnerIndexMismatch,
+    
+    #[msg("SlotHashes sysvar unavailable")]
+    SlotHashesUnavailable,
+    
+    #[msg("Invalid subscription plan")]
+    InvalidPlan,
+    
+    #[msg("No claim credits remaining")]
+    NoClaimCredits,
+    
+    #[msg("Too many allowed buyers")]
+    TooManyAllowedBuyers,
+    
+    #[msg("Listing account required")]
+    ListingRequired,
+    
+    #[msg("Claim commitment reveal window has not ended")]
+    CommitmentNotExpired,
+    
+    #[msg("Organization claims can only be sold for MSC")]
+    OrgListingRequiresMsc,
+    
+    #[msg("Co-owner account required")]
+    CoOwnersRequired,
+    
+    #[msg("Proposal belongs to a previous co-owner set")]
+    StaleProposal,
+    
+    #[msg("License offer creator no longer manages the claim")]
+    LicenseOfferInvalidated,
 }
\ No newline at end of file
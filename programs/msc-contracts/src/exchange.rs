@@ -66,13 +66,15 @@ pub fn swap_msc_to_usdc(
     
     token::transfer(cpi_ctx_msc, msc_amount)?;
     
-    // 执行 USDC 转账给用户 (简化处理，实际需要池子作为权限)
+    // 执行 USDC 转账给用户 (由兑换池 PDA 签名)
+    let pool_seeds: &[&[u8]] = &[b"exchange_pool", &[ctx.bumps.exchange_pool]];
+    let signer_seeds = &[pool_seeds];
     let cpi_accounts_usdc = Transfer {
         from: ctx.accounts.pool_usdc_vault.to_account_info(),
         to: ctx.accounts.user_usdc_account.to_account_info(),
         authority: ctx.accounts.pool_authority.to_account_info(),
     };
-    let cpi_ctx_usdc = CpiContext::new(cpi_program, cpi_accounts_usdc);
+    let cpi_ctx_usdc = CpiContext::new_with_signer(cpi_program, cpi_accounts_usdc, signer_seeds);
     
     token::transfer(cpi_ctx_usdc, usdc_amount)?;
     
@@ -137,6 +139,10 @@ pub fn update_exchange_rate(
 pub fn initialize_exchange_pool(
     ctx: Context<InitializeExchangePool>,
 ) -> Result<()> {
+    let config = &ctx.accounts.config;
+    require!(config.is_initialized, MscError::AccountNotInitialized);
+    require!(ctx.accounts.authority.key() == config.authority, MscError::InvalidAuthority);
+    
     let exchange_pool = &mut ctx.accounts.exchange_pool;
     
     exchange_pool.authority = ctx.accounts.authority.key();
@@ -163,7 +169,11 @@ pub fn initialize_exchange_pool(
 
 #[derive(Accounts)]
 pub struct SwapMscToUsdc<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"exchange_pool"],
+        bump
+    )]
     pub exchange_pool: Account<'info, ExchangePool>,
     
     #[account(
@@ -180,19 +190,36 @@ pub struct SwapMscToUsdc<'info> {
     )]
     pub user_stats: Account<'info, UserStats>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = user_msc_account.mint == exchange_pool.msc_mint @ MscError::InvalidMint,
+        constraint = user_msc_account.owner == user.key() @ MscError::InvalidAccountOwner
+    )]
     pub user_msc_account: Account<'info, TokenAccount>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = user_usdc_account.mint == exchange_pool.usdc_mint @ MscError::InvalidMint,
+        constraint = user_usdc_account.owner == user.key() @ MscError::InvalidAccountOwner
+    )]
     pub user_usdc_account: Account<'info, TokenAccount>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        address = exchange_pool.msc_vault @ MscError::InvalidRecipient,
+        constraint = pool_msc_vault.owner == exchange_pool.key() @ MscError::InvalidAccountOwner
+    )]
     pub pool_msc_vault: Account<'info, TokenAccount>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        address = exchange_pool.usdc_vault @ MscError::InvalidRecipient,
+        constraint = pool_usdc_vault.owner == exchange_pool.key() @ MscError::InvalidAccountOwner
+    )]
     pub pool_usdc_vault: Account<'info, TokenAccount>,
     
-    /// CHECK: Pool authority for token transfers
+    /// CHECK: 必须是兑换池 PDA，由其签名支付 USDC
+    #[account(address = exchange_pool.key() @ MscError::InvalidAuthority)]
     pub pool_authority: AccountInfo<'info>,
     
     #[account(mut)]
@@ -216,6 +243,12 @@ pub struct UpdateExchangeRate<'info> {
 
 #[derive(Accounts)]
 pub struct InitializeExchangePool<'info> {
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Account<'info, MscTokenConfig>,
+    
     #[account(
         init,
         payer = authority,
@@ -225,13 +258,20 @@ pub struct InitializeExchangePool<'info> {
     )]
     pub exchange_pool: Account<'info, ExchangePool>,
     
+    #[account(address = config.mint @ MscError::InvalidMint)]
     pub msc_mint: Account<'info, anchor_spl::token::Mint>,
     pub usdc_mint: Account<'info, anchor_spl::token::Mint>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = msc_vault.mint == msc_mint.key() @ MscError::InvalidMint
+    )]
     pub msc_vault: Account<'info, TokenAccount>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = usdc_vault.mint == usdc_mint.key() @ MscError::InvalidMint
+    )]
     pub usdc_vault: Account<'info, TokenAccount>,
     
     #[account(mut)]
@@ -265,4 +305,33 @@ pub fn calculate_swap_output(
         .ok_or(MscError::MathUnderflow)?;
     
     Ok((output_amount, fee_amount))
-}
\ No newline at end of file
+}
+
+// 计算经兑换池换得指定数量 MSC 所需支付的 USDC (按池子当前汇率，手续费另加，均向上取整)
+pub fn calculate_swap_input(
+    msc_amount: u64,
+    exchange_rate: u64,
+    fee_rate: u16,
+) -> Result<(u64, u64)> {
+    let input_before_fee = msc_amount
+        .checked_mul(exchange_rate)
+        .ok_or(MscError::MathOverflow)?
+        .checked_add(999_999)
+        .ok_or(MscError::MathOverflow)?
+        .checked_div(1_000_000)
+        .ok_or(MscError::DivisionByZero)?;
+    
+    let fee_amount = input_before_fee
+        .checked_mul(fee_rate as u64)
+        .ok_or(MscError::MathOverflow)?
+        .checked_add(9_999)
+        .ok_or(MscError::MathOverflow)?
+        .checked_div(10_000)
+        .ok_or(MscError::DivisionByZero)?;
+    
+    let input_amount = input_before_fee
+        .checked_add(fee_amount)
+        .ok_or(MscError::MathOverflow)?;
+    
+    Ok((input_amount, fee_amount))
+}
@@ -1,6 +1,6 @@
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Mint};
-use anchor_spl::associated_token::AssociatedToken;
 
 declare_id!("F61oRxmdwKKuHcN1rNRshKQDnAQAeqduitwb1sY2J4Yd");
 
@@ -9,6 +9,20 @@ pub mod msc_token;
 pub mod ownership;
 pub mod service;
 pub mod exchange;
+pub mod co_ownership;
+pub mod notary;
+pub mod ed25519;
+pub mod batch;
+pub mod compression;
+pub mod certificate;
+pub mod commitment;
+pub mod renewal;
+pub mod dispute;
+pub mod licensing;
+pub mod marketplace;
+pub mod organization;
+pub mod owner_index;
+pub mod subscription;
 pub mod state;
 pub mod error;
 
@@ -16,8 +30,20 @@ use msc_token::*;
 use ownership::*;
 use service::*;
 use exchange::*;
-use state::*;
-use error::*;
+use co_ownership::*;
+use notary::*;
+use batch::*;
+use compression::*;
+use certificate::*;
+use commitment::*;
+use renewal::*;
+use dispute::*;
+use licensing::*;
+use marketplace::*;
+use organization::*;
+use owner_index::*;
+use subscription::*;
+use state::{ClaimAction, ClaimRelation, ClaimVerification, CoOwnerShare, CompressedClaim, DisputeOutcome, LicenseVerification, ListingCurrency, OwnerIndexView};
 
 #[program]
 pub mod msc_contracts {
@@ -52,16 +78,472 @@ pub mod msc_contracts {
         msc_token::batch_airdrop(ctx, amounts)
     }
 
+    // 全局回执计数器
+    pub fn initialize_global_state(
+        ctx: Context<InitializeGlobalState>,
+    ) -> Result<()> {
+        service::initialize_global_state(ctx)
+    }
+
+    // 服务配置
+    pub fn initialize_service_config(
+        ctx: Context<InitializeServiceConfig>,
+        service_id: u8,
+        price: u64,
+        treasury: Pubkey,
+        name: String,
+    ) -> Result<()> {
+        service::initialize_service_config(ctx, service_id, price, treasury, name)
+    }
+
+    pub fn update_service_config(
+        ctx: Context<UpdateServiceConfig>,
+        price: u64,
+        is_enabled: bool,
+        treasury: Pubkey,
+        name: String,
+    ) -> Result<()> {
+        service::update_service_config(ctx, price, is_enabled, treasury, name)
+    }
+
     // 支付并创建确权记录（原子操作）
     pub fn pay_and_create_claim(
         ctx: Context<PayAndCreateClaim>,
-        amount: u64,
+        max_price: u64,
+        file_hash: String,
+        relation: ClaimRelation,
+    ) -> Result<()> {
+        service::pay_and_create_claim(ctx, max_price, file_hash, relation)
+    }
+
+    // 中继代付确权 (用户无需持有 SOL)
+    pub fn relayed_pay_and_create_claim(
+        ctx: Context<RelayedPayAndCreateClaim>,
+        max_price: u64,
+        file_hash: String,
+        relation: ClaimRelation,
+        relayer_fee: u64,
+    ) -> Result<()> {
+        service::relayed_pay_and_create_claim(ctx, max_price, file_hash, relation, relayer_fee)
+    }
+
+    // 以 USDC 经兑换池支付并创建确权记录
+    pub fn exchange_pay_and_create_claim(
+        ctx: Context<ExchangePayAndCreateClaim>,
+        max_usdc_amount: u64,
+        file_hash: String,
+        relation: ClaimRelation,
+    ) -> Result<()> {
+        service::exchange_pay_and_create_claim(ctx, max_usdc_amount, file_hash, relation)
+    }
+
+    // 以组织为所有者支付并创建确权记录
+    pub fn pay_and_create_org_claim(
+        ctx: Context<PayAndCreateOrgClaim>,
+        max_price: u64,
+        file_hash: String,
+        relation: ClaimRelation,
+    ) -> Result<()> {
+        organization::pay_and_create_org_claim(ctx, max_price, file_hash, relation)
+    }
+
+    // 先提交承诺再揭示的确权 (防抢注)
+    pub fn commit_claim(
+        ctx: Context<CommitClaim>,
+        commitment: [u8; 32],
+        max_price: u64,
+    ) -> Result<()> {
+        commitment::commit_claim(ctx, commitment, max_price)
+    }
+
+    pub fn reveal_claim(
+        ctx: Context<RevealClaim>,
+        file_hash: String,
+        salt: [u8; 32],
+        relation: ClaimRelation,
+    ) -> Result<()> {
+        commitment::reveal_claim(ctx, file_hash, salt, relation)
+    }
+
+    pub fn expire_commitment(
+        ctx: Context<ExpireCommitment>,
+    ) -> Result<()> {
+        commitment::expire_commitment(ctx)
+    }
+
+    // 两阶段支付 (escrow 托管，完成或退款)
+    pub fn pay_for_service(
+        ctx: Context<PayForService>,
+        service_id: u8,
+        payment_id: u64,
+        max_price: u64,
+    ) -> Result<()> {
+        service::pay_for_service(ctx, service_id, payment_id, max_price)
+    }
+
+    pub fn fulfill_payment(
+        ctx: Context<FulfillPayment>,
+    ) -> Result<()> {
+        service::fulfill_payment(ctx)
+    }
+
+    pub fn refund_payment(
+        ctx: Context<RefundPayment>,
+    ) -> Result<()> {
+        service::refund_payment(ctx)
+    }
+
+    // 公证确权
+    pub fn add_notary(
+        ctx: Context<AddNotary>,
+        notary: Pubkey,
+    ) -> Result<()> {
+        notary::add_notary(ctx, notary)
+    }
+
+    pub fn remove_notary(
+        ctx: Context<RemoveNotary>,
+    ) -> Result<()> {
+        notary::remove_notary(ctx)
+    }
+
+    pub fn pay_and_create_notarized_claim(
+        ctx: Context<PayAndCreateNotarizedClaim>,
+        max_price: u64,
         file_hash: String,
+        relation: ClaimRelation,
+        attested_at: i64,
+    ) -> Result<()> {
+        notary::pay_and_create_notarized_claim(ctx, max_price, file_hash, relation, attested_at)
+    }
+
+    // 批量确权 (Merkle 根锚定)
+    pub fn create_batch_claim(
+        ctx: Context<CreateBatchClaim>,
+        merkle_root: [u8; 32],
+        count: u32,
+        max_price: u64,
+    ) -> Result<()> {
+        batch::create_batch_claim(ctx, merkle_root, count, max_price)
+    }
+
+    pub fn verify_batch_membership(
+        ctx: Context<VerifyBatchMembership>,
+        file_hash: String,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        batch::verify_batch_membership(ctx, file_hash, proof)
+    }
+
+    // 压缩确权 (并发 Merkle 树)
+    pub fn create_claim_tree(
+        ctx: Context<CreateClaimTree>,
+        max_depth: u32,
+        max_buffer_size: u32,
+    ) -> Result<()> {
+        compression::create_claim_tree(ctx, max_depth, max_buffer_size)
+    }
+
+    pub fn pay_and_create_compressed_claim(
+        ctx: Context<PayAndCreateCompressedClaim>,
+        max_price: u64,
+        file_hash: String,
+    ) -> Result<()> {
+        compression::pay_and_create_compressed_claim(ctx, max_price, file_hash)
+    }
+
+    pub fn transfer_compressed_claim<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateCompressedClaim<'info>>,
+        root: [u8; 32],
+        claim: CompressedClaim,
+        new_owner: Pubkey,
+    ) -> Result<()> {
+        compression::transfer_compressed_claim(ctx, root, claim, new_owner)
+    }
+
+    pub fn revoke_compressed_claim<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateCompressedClaim<'info>>,
+        root: [u8; 32],
+        claim: CompressedClaim,
+    ) -> Result<()> {
+        compression::revoke_compressed_claim(ctx, root, claim)
+    }
+
+    // 确权元数据
+    pub fn set_claim_metadata(
+        ctx: Context<SetClaimMetadata>,
+        title: String,
+        content_type: String,
+        uri: String,
+        license: String,
+    ) -> Result<()> {
+        ownership::set_claim_metadata(ctx, title, content_type, uri, license)
+    }
+
+    pub fn update_claim_metadata(
+        ctx: Context<UpdateClaimMetadata>,
+        title: String,
+        content_type: String,
+        uri: String,
+        license: String,
+    ) -> Result<()> {
+        ownership::update_claim_metadata(ctx, title, content_type, uri, license)
+    }
+
+    // 确权验证 (返回 ClaimVerification)
+    pub fn verify_claim(
+        ctx: Context<VerifyClaim>,
+        file_hash: String,
+    ) -> Result<ClaimVerification> {
+        ownership::verify_claim(ctx, file_hash)
+    }
+
+    // 确权续期与过期关闭
+    pub fn renew_claim(
+        ctx: Context<RenewClaim>,
+        max_price: u64,
+    ) -> Result<()> {
+        renewal::renew_claim(ctx, max_price)
+    }
+
+    pub fn close_expired_claim(
+        ctx: Context<CloseExpiredClaim>,
+    ) -> Result<()> {
+        renewal::close_expired_claim(ctx)
+    }
+
+    // 确权争议与仲裁
+    pub fn add_arbiter(
+        ctx: Context<AddArbiter>,
+        arbiter: Pubkey,
     ) -> Result<()> {
-        service::pay_and_create_claim(ctx, amount, file_hash)
+        dispute::add_arbiter(ctx, arbiter)
+    }
+
+    pub fn remove_arbiter(
+        ctx: Context<RemoveArbiter>,
+    ) -> Result<()> {
+        dispute::remove_arbiter(ctx)
+    }
+
+    pub fn open_dispute(
+        ctx: Context<OpenDispute>,
+        evidence_uri: String,
+        evidence_hash: [u8; 32],
+        max_bond: u64,
+    ) -> Result<()> {
+        dispute::open_dispute(ctx, evidence_uri, evidence_hash, max_bond)
+    }
+
+    pub fn resolve_dispute(
+        ctx: Context<ResolveDispute>,
+        outcome: DisputeOutcome,
+    ) -> Result<()> {
+        dispute::resolve_dispute(ctx, outcome)
+    }
+
+    // 作品授权
+    pub fn create_license_offer(
+        ctx: Context<CreateLicenseOffer>,
+        offer_id: u32,
+        price: u64,
+        duration: i64,
+        transferable: bool,
+        max_licenses: u32,
+    ) -> Result<()> {
+        licensing::create_license_offer(ctx, offer_id, price, duration, transferable, max_licenses)
+    }
+
+    pub fn purchase_license<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PurchaseLicense<'info>>,
+    ) -> Result<()> {
+        licensing::purchase_license(ctx)
+    }
+
+    pub fn transfer_license(
+        ctx: Context<TransferLicense>,
+        new_holder: Pubkey,
+    ) -> Result<()> {
+        licensing::transfer_license(ctx, new_holder)
+    }
+
+    pub fn verify_license(
+        ctx: Context<VerifyLicense>,
+        holder: Pubkey,
+    ) -> Result<LicenseVerification> {
+        licensing::verify_license(ctx, holder)
+    }
+
+    // 订阅套餐与确权次数
+    pub fn create_plan(
+        ctx: Context<CreatePlan>,
+        plan_id: u16,
+        price: u64,
+        credits: u32,
+        duration: i64,
+    ) -> Result<()> {
+        subscription::create_plan(ctx, plan_id, price, credits, duration)
+    }
+
+    pub fn update_plan(
+        ctx: Context<UpdatePlan>,
+        price: u64,
+        credits: u32,
+        duration: i64,
+        is_enabled: bool,
+    ) -> Result<()> {
+        subscription::update_plan(ctx, price, credits, duration, is_enabled)
+    }
+
+    pub fn create_subscription(
+        ctx: Context<CreateSubscription>,
+    ) -> Result<()> {
+        subscription::create_subscription(ctx)
+    }
+
+    pub fn purchase_plan(
+        ctx: Context<PurchasePlan>,
+        max_price: u64,
+    ) -> Result<()> {
+        subscription::purchase_plan(ctx, max_price)
+    }
+
+    // 所有者确权索引
+    pub fn get_owner_index_page(
+        ctx: Context<GetOwnerIndexPage>,
+    ) -> Result<OwnerIndexView> {
+        owner_index::get_owner_index_page(ctx)
+    }
+
+    // 组织与成员管理
+    pub fn create_organization(
+        ctx: Context<CreateOrganization>,
+        name: String,
+    ) -> Result<()> {
+        organization::create_organization(ctx, name)
+    }
+
+    pub fn add_org_admin(
+        ctx: Context<UpdateOrgAdmins>,
+        admin: Pubkey,
+    ) -> Result<()> {
+        organization::add_org_admin(ctx, admin)
+    }
+
+    pub fn remove_org_admin(
+        ctx: Context<UpdateOrgAdmins>,
+        admin: Pubkey,
+    ) -> Result<()> {
+        organization::remove_org_admin(ctx, admin)
+    }
+
+    pub fn add_org_member(
+        ctx: Context<AddOrgMember>,
+        member: Pubkey,
+    ) -> Result<()> {
+        organization::add_org_member(ctx, member)
+    }
+
+    pub fn remove_org_member(
+        ctx: Context<RemoveOrgMember>,
+    ) -> Result<()> {
+        organization::remove_org_member(ctx)
+    }
+
+    pub fn withdraw_org_treasury(
+        ctx: Context<WithdrawOrgTreasury>,
+        amount: u64,
+    ) -> Result<()> {
+        organization::withdraw_org_treasury(ctx, amount)
+    }
+
+    // 确权交易市场
+    pub fn list_claim(
+        ctx: Context<ListClaim>,
+        price: u64,
+        currency: ListingCurrency,
+        expires_at: i64,
+        allowed_buyers: Vec<Pubkey>,
+    ) -> Result<()> {
+        marketplace::list_claim(ctx, price, currency, expires_at, allowed_buyers)
+    }
+
+    pub fn cancel_listing(
+        ctx: Context<CancelListing>,
+    ) -> Result<()> {
+        marketplace::cancel_listing(ctx)
+    }
+
+    pub fn purchase_listing(
+        ctx: Context<PurchaseListing>,
+    ) -> Result<()> {
+        marketplace::purchase_listing(ctx)
+    }
+
+    // 确权证书 NFT 持有人同步为所有者
+    pub fn sync_claim_owner(
+        ctx: Context<SyncClaimOwner>,
+    ) -> Result<()> {
+        certificate::sync_claim_owner(ctx)
+    }
+
+    // 确权转让与撤销 (单一所有者)
+    pub fn transfer_claim(
+        ctx: Context<TransferClaim>,
+        new_owner: Pubkey,
+    ) -> Result<()> {
+        ownership::transfer_claim(ctx, new_owner)
+    }
+
+    pub fn revoke_claim(
+        ctx: Context<RevokeClaim>,
+    ) -> Result<()> {
+        ownership::revoke_claim(ctx)
+    }
+
+    // 共有确权
+    pub fn set_co_owners(
+        ctx: Context<SetCoOwners>,
+        co_owners: Vec<CoOwnerShare>,
+        approval_threshold: u8,
+    ) -> Result<()> {
+        co_ownership::set_co_owners(ctx, co_owners, approval_threshold)
+    }
+
+    pub fn transfer_share(
+        ctx: Context<TransferShare>,
+        share_bps: u16,
+        recipient: Pubkey,
+    ) -> Result<()> {
+        co_ownership::transfer_share(ctx, share_bps, recipient)
+    }
+
+    pub fn propose_claim_action(
+        ctx: Context<ProposeClaimAction>,
+        action: ClaimAction,
+    ) -> Result<()> {
+        co_ownership::propose_claim_action(ctx, action)
+    }
+
+    pub fn approve_claim_action(
+        ctx: Context<ApproveClaimAction>,
+    ) -> Result<()> {
+        co_ownership::approve_claim_action(ctx)
+    }
+
+    pub fn execute_claim_action(
+        ctx: Context<ExecuteClaimAction>,
+    ) -> Result<()> {
+        co_ownership::execute_claim_action(ctx)
     }
 
     // 兑换合约功能
+    pub fn initialize_exchange_pool(
+        ctx: Context<InitializeExchangePool>,
+    ) -> Result<()> {
+        exchange::initialize_exchange_pool(ctx)
+    }
+
     pub fn swap_msc_to_usdc(
         ctx: Context<SwapMscToUsdc>,
         msc_amount: u64,
@@ -0,0 +1,387 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::state::*;
+use crate::error::*;
+use crate::organization::require_claim_authority;
+use crate::service::{charge_service_fee, SERVICE_CLAIM};
+
+// 授权销售的协议手续费 (基点)，划入协议金库
+pub const LICENSE_PROTOCOL_FEE_BPS: u64 = 250;
+
+// 创建授权要约 (单一所有者、任一共有人或组织管理员)
+pub fn create_license_offer(
+    ctx: Context<CreateLicenseOffer>,
+    offer_id: u32,
+    price: u64,
+    duration: i64,
+    transferable: bool,
+    max_licenses: u32,
+) -> Result<()> {
+    require!(duration >= 0, MscError::InvalidTimestamp);
+    
+    let creator = ctx.accounts.owner.key();
+    let claim = &ctx.accounts.claim;
+    let clock = Clock::get()?;
+    require!(claim.is_active, MscError::ClaimNotFound);
+    require!(!claim.is_disputed, MscError::ClaimDisputed);
+    require!(!claim.is_expired(clock.unix_timestamp), MscError::ClaimExpired);
+    if claim.is_co_owned {
+        let co_owners = ctx.accounts.co_owners.as_ref().ok_or(MscError::NotCoOwner)?;
+        require!(co_owners.is_co_owner(&creator), MscError::NotCoOwner);
+    } else {
+        require_claim_authority(claim, &creator, ctx.accounts.organization.as_deref())?;
+    }
+    
+    let offer = &mut ctx.accounts.license_offer;
+    offer.claim = claim.key();
+    offer.offer_id = offer_id;
+    offer.price = price;
+    offer.duration = duration;
+    offer.transferable = transferable;
+    offer.max_licenses = max_licenses;
+    offer.licenses_sold = 0;
+    offer.created_by = creator;
+    offer.created_at = clock.unix_timestamp;
+    
+    msg!("License offer created:");
+    msg!("Claim: {}", offer.claim);
+    msg!("Offer ID: {}", offer_id);
+    msg!("Price: {} MSC", price);
+    msg!("Duration: {}", duration);
+    msg!("Max Licenses: {}", max_licenses);
+    
+    Ok(())
+}
+
+// 购买授权：扣除协议手续费后支付给所有者，共有确权按份额分配
+// 共有确权时，各共有人的 MSC 代币账户按 co_owners 顺序通过 remaining_accounts 传入
+pub fn purchase_license<'info>(
+    ctx: Context<'_, '_, 'info, 'info, PurchaseLicense<'info>>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let claim = &ctx.accounts.claim;
+    require!(claim.is_active, MscError::ClaimNotFound);
+    require!(!claim.is_disputed, MscError::ClaimDisputed);
+    require!(!claim.is_expired(clock.unix_timestamp), MscError::ClaimExpired);
+    
+    let offer = &ctx.accounts.license_offer;
+    require!(
+        offer.max_licenses == 0 || offer.licenses_sold < offer.max_licenses,
+        MscError::LicenseSoldOut
+    );
+    
+    // 要约创建者须仍有权管理确权，所有权变更后旧要约失效
+    // 由组织管理员创建时所有者为组织 PDA，收益须划入组织金库
+    let mut org_treasury = None;
+    if claim.is_co_owned {
+        let co_owners = ctx.accounts.co_owners.as_ref().ok_or(MscError::NotCoOwner)?;
+        require!(co_owners.is_co_owner(&offer.created_by), MscError::LicenseOfferInvalidated);
+    } else if offer.created_by != claim.owner {
+        let organization = ctx
+            .accounts
+            .organization
+            .as_ref()
+            .ok_or(MscError::LicenseOfferInvalidated)?;
+        require!(
+            organization.key() == claim.owner && organization.is_admin(&offer.created_by),
+            MscError::LicenseOfferInvalidated
+        );
+        org_treasury = Some(organization.treasury);
+    }
+    
+    let mint = ctx.accounts.config.mint;
+    let buyer_token_account = &ctx.accounts.buyer_token_account;
+    require!(buyer_token_account.mint == mint, MscError::InvalidMint);
+    require!(
+        buyer_token_account.owner == ctx.accounts.buyer.key(),
+        MscError::InvalidAccountOwner
+    );
+    require!(buyer_token_account.amount >= offer.price, MscError::InsufficientBalance);
+    
+    let treasury_token_account = &ctx.accounts.treasury_token_account;
+    require!(
+        treasury_token_account.key() == ctx.accounts.service_config.treasury,
+        MscError::InvalidTreasury
+    );
+    require!(treasury_token_account.mint == mint, MscError::InvalidMint);
+    
+    let price = offer.price;
+    let protocol_fee = price
+        .checked_mul(LICENSE_PROTOCOL_FEE_BPS)
+        .ok_or(MscError::MathOverflow)?
+        .checked_div(10_000)
+        .ok_or(MscError::DivisionByZero)?;
+    let owner_proceeds = price.checked_sub(protocol_fee).ok_or(MscError::MathUnderflow)?;
+    
+    let buyer = ctx.accounts.buyer.to_account_info();
+    if protocol_fee > 0 {
+        charge_service_fee(
+            &ctx.accounts.token_program,
+            buyer_token_account,
+            treasury_token_account,
+            buyer.clone(),
+            protocol_fee,
+        )?;
+    }
+    
+    if claim.is_co_owned {
+        let co_owners = ctx.accounts.co_owners.as_ref().ok_or(MscError::NotCoOwner)?;
+        require!(
+            ctx.remaining_accounts.len() == co_owners.co_owners.len(),
+            MscError::InvalidRecipient
+        );
+        
+        // 按份额分配，舍入余数归最后一位共有人
+        let mut distributed: u64 = 0;
+        let last = co_owners.co_owners.len() - 1;
+        for (index, (share, info)) in co_owners
+            .co_owners
+            .iter()
+            .zip(ctx.remaining_accounts.iter())
+            .enumerate()
+        {
+            let payee = Account::<TokenAccount>::try_from(info)?;
+            require!(payee.mint == mint, MscError::InvalidMint);
+            require!(payee.owner == share.owner, MscError::InvalidRecipient);
+            
+            let amount = if index == last {
+                owner_proceeds.checked_sub(distributed).ok_or(MscError::MathUnderflow)?
+            } else {
+                owner_proceeds
+                    .checked_mul(share.share_bps as u64)
+                    .ok_or(MscError::MathOverflow)?
+                    .checked_div(ClaimCoOwners::TOTAL_SHARE_BPS as u64)
+                    .ok_or(MscError::DivisionByZero)?
+            };
+            distributed = distributed.checked_add(amount).ok_or(MscError::MathOverflow)?;
+            
+            if amount > 0 {
+                charge_service_fee(
+                    &ctx.accounts.token_program,
+                    buyer_token_account,
+                    &payee,
+                    buyer.clone(),
+                    amount,
+                )?;
+            }
+        }
+    } else {
+        let owner_token_account = ctx
+            .accounts
+            .owner_token_account
+            .as_ref()
+            .ok_or(MscError::InvalidRecipient)?;
+        require!(owner_token_account.mint == mint, MscError::InvalidMint);
+        require!(owner_token_account.owner == claim.owner, MscError::InvalidRecipient);
+        if let Some(org_treasury) = org_treasury {
+            require!(owner_token_account.key() == org_treasury, MscError::InvalidRecipient);
+        }
+        
+        if owner_proceeds > 0 {
+            charge_service_fee(
+                &ctx.accounts.token_program,
+                buyer_token_account,
+                owner_token_account,
+                buyer,
+                owner_proceeds,
+            )?;
+        }
+    }
+    
+    let expires_at = if offer.duration == 0 {
+        0
+    } else {
+        clock
+            .unix_timestamp
+            .checked_add(offer.duration)
+            .ok_or(MscError::MathOverflow)?
+    };
+    
+    let license = &mut ctx.accounts.license;
+    license.offer = offer.key();
+    license.claim = claim.key();
+    license.holder = ctx.accounts.buyer.key();
+    license.price_paid = price;
+    license.purchased_at = clock.unix_timestamp;
+    license.expires_at = expires_at;
+    license.transferable = offer.transferable;
+    
+    let offer = &mut ctx.accounts.license_offer;
+    offer.licenses_sold = offer.licenses_sold.checked_add(1).ok_or(MscError::MathOverflow)?;
+    
+    msg!("License purchased:");
+    msg!("Claim: {}", license.claim);
+    msg!("Holder: {}", license.holder);
+    msg!("Price: {} MSC", price);
+    msg!("Protocol Fee: {} MSC", protocol_fee);
+    msg!("Expires At: {}", expires_at);
+    
+    Ok(())
+}
+
+// 转让授权 (仅限可转让授权的当前持有人)
+// 授权 PDA 以原购买人派生，转让后地址不变，seeds 中的买家与 holder 不再一致
+pub fn transfer_license(
+    ctx: Context<TransferLicense>,
+    new_holder: Pubkey,
+) -> Result<()> {
+    let license = &mut ctx.accounts.license;
+    require!(license.transferable, MscError::LicenseNotTransferable);
+    require!(license.holder == ctx.accounts.holder.key(), MscError::LicenseHolderMismatch);
+    require!(new_holder != Pubkey::default(), MscError::InvalidRecipient);
+    
+    let old_holder = license.holder;
+    license.holder = new_holder;
+    
+    msg!("License transferred:");
+    msg!("License: {}", license.key());
+    msg!("From: {}", old_holder);
+    msg!("To: {}", new_holder);
+    
+    Ok(())
+}
+
+// 验证授权 (无需权限，结果通过 return data 返回)
+// 授权须属于 holder、未过期，且对应确权有效、未过期、无未裁决争议
+pub fn verify_license(
+    ctx: Context<VerifyLicense>,
+    holder: Pubkey,
+) -> Result<LicenseVerification> {
+    let license = &ctx.accounts.license;
+    let claim = &ctx.accounts.claim;
+    let clock = Clock::get()?;
+    
+    let is_valid = license.holder == holder
+        && license.is_valid(clock.unix_timestamp)
+        && claim.status(clock.unix_timestamp) == ClaimStatus::Active;
+    
+    let verification = LicenseVerification {
+        license: license.key(),
+        claim: license.claim,
+        holder: license.holder,
+        expires_at: license.expires_at,
+        is_valid,
+    };
+    
+    msg!("License verified:");
+    msg!("License: {}", verification.license);
+    msg!("Holder: {}", verification.holder);
+    msg!("Valid: {}", is_valid);
+    
+    Ok(verification)
+}
+
+// Account 结构定义
+
+#[derive(Accounts)]
+#[instruction(offer_id: u32)]
+pub struct CreateLicenseOffer<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = LicenseOffer::LEN,
+        seeds = [b"license_offer", claim.key().as_ref(), offer_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub license_offer: Account<'info, LicenseOffer>,
+    
+    pub claim: Account<'info, OwnershipClaim>,
+    
+    // 共有确权时传入
+    #[account(
+        seeds = [b"co_owners", claim.key().as_ref()],
+        bump
+    )]
+    pub co_owners: Option<Account<'info, ClaimCoOwners>>,
+    
+    // 组织确权时传入，签名者须为组织管理员
+    #[account(
+        seeds = [b"organization", organization.name.as_bytes()],
+        bump
+    )]
+    pub organization: Option<Box<Account<'info, Organization>>>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PurchaseLicense<'info> {
+    #[account(
+        init,
+        payer = buyer,
+        space = License::LEN,
+        seeds = [b"license", license_offer.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub license: Box<Account<'info, License>>,
+    
+    #[account(
+        mut,
+        seeds = [b"license_offer", claim.key().as_ref(), license_offer.offer_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub license_offer: Box<Account<'info, LicenseOffer>>,
+    
+    pub claim: Box<Account<'info, OwnershipClaim>>,
+    
+    // 共有确权时传入
+    #[account(
+        seeds = [b"co_owners", claim.key().as_ref()],
+        bump
+    )]
+    pub co_owners: Option<Box<Account<'info, ClaimCoOwners>>>,
+    
+    // 组织确权时传入，用于核验要约创建者仍为组织管理员，收益划入组织金库
+    #[account(
+        seeds = [b"organization", organization.name.as_bytes()],
+        bump
+    )]
+    pub organization: Option<Box<Account<'info, Organization>>>,
+    
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Box<Account<'info, MscTokenConfig>>,
+    
+    // 协议金库以确权服务配置登记的金库为准
+    #[account(
+        seeds = [b"service_config", [SERVICE_CLAIM].as_ref()],
+        bump
+    )]
+    pub service_config: Box<Account<'info, ServiceConfig>>,
+    
+    #[account(mut)]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    
+    // 单一所有者确权时传入
+    #[account(mut)]
+    pub owner_token_account: Option<Account<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TransferLicense<'info> {
+    #[account(mut)]
+    pub license: Account<'info, License>,
+    pub holder: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyLicense<'info> {
+    pub license: Account<'info, License>,
+    
+    #[account(address = license.claim)]
+    pub claim: Account<'info, OwnershipClaim>,
+}
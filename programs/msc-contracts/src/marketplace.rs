@@ -0,0 +1,348 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::state::*;
+use crate::error::*;
+use crate::owner_index::transfer_indexed_claim;
+use crate::organization::require_claim_authority;
+use crate::service::{charge_service_fee, SERVICE_CLAIM, SERVICE_MARKETPLACE_USDC};
+
+// 确权交易的协议手续费 (基点)
+pub const MARKETPLACE_FEE_BPS: u64 = 250;
+
+// 协议手续费的收款服务配置：MSC 计价为确权服务金库，USDC 计价为 USDC 交易服务金库
+pub fn fee_service_id(currency: ListingCurrency) -> u8 {
+    match currency {
+        ListingCurrency::Msc => SERVICE_CLAIM,
+        ListingCurrency::Usdc => SERVICE_MARKETPLACE_USDC,
+    }
+}
+
+// 挂牌出售确权 (仅限单一所有者或组织管理员，挂牌期间确权锁定)
+// MSC 计价以代币配置的 mint 为准，USDC 计价以兑换池登记的 USDC mint 为准
+pub fn list_claim(
+    ctx: Context<ListClaim>,
+    price: u64,
+    currency: ListingCurrency,
+    expires_at: i64,
+    allowed_buyers: Vec<Pubkey>,
+) -> Result<()> {
+    require!(price > 0, MscError::PaymentAmountTooLow);
+    require!(
+        allowed_buyers.len() <= Listing::MAX_ALLOWED_BUYERS,
+        MscError::TooManyAllowedBuyers
+    );
+    
+    let clock = Clock::get()?;
+    require!(
+        expires_at == 0 || expires_at > clock.unix_timestamp,
+        MscError::InvalidTimestamp
+    );
+    
+    let payment_mint = match currency {
+        ListingCurrency::Msc => ctx.accounts.config.mint,
+        ListingCurrency::Usdc => {
+            let exchange_pool = ctx
+                .accounts
+                .exchange_pool
+                .as_ref()
+                .ok_or(MscError::ExchangePoolNotActive)?;
+            exchange_pool.usdc_mint
+        }
+    };
+    
+    let claim = &mut ctx.accounts.claim;
+    require!(claim.is_active, MscError::ClaimNotFound);
+    require_claim_authority(claim, &ctx.accounts.owner.key(), ctx.accounts.organization.as_deref())?;
+    // 组织确权以组织为卖家，售价只能划入组织 MSC 金库，否则无法经 withdraw_org_treasury 取出
+    let seller = claim.owner;
+    let seller_treasury = match ctx.accounts.organization.as_ref() {
+        Some(organization) if organization.key() == seller => {
+            require!(currency == ListingCurrency::Msc, MscError::OrgListingRequiresMsc);
+            Some(organization.treasury)
+        }
+        _ => None,
+    };
+    require!(!claim.is_co_owned, MscError::ClaimCoOwned);
+    require!(claim.certificate_mint.is_none(), MscError::ClaimHasCertificate);
+    require!(!claim.is_disputed, MscError::ClaimDisputed);
+    require!(!claim.is_expired(clock.unix_timestamp), MscError::ClaimExpired);
+    
+    claim.is_listed = true;
+    
+    let listing = &mut ctx.accounts.listing;
+    listing.claim = claim.key();
+    listing.seller = seller;
+    listing.price = price;
+    listing.currency = currency;
+    listing.payment_mint = payment_mint;
+    listing.seller_treasury = seller_treasury;
+    listing.allowed_buyers = allowed_buyers;
+    listing.expires_at = expires_at;
+    listing.created_at = clock.unix_timestamp;
+    
+    msg!("Claim listed:");
+    msg!("Claim: {}", listing.claim);
+    msg!("Seller: {}", seller);
+    msg!("Price: {}", price);
+    msg!("Payment Mint: {}", payment_mint);
+    msg!("Expires At: {}", expires_at);
+    
+    Ok(())
+}
+
+// 撤销挂牌并解锁确权 (卖家随时可撤，挂牌过期或确权已过宽限期后任何人可清理)
+// 确权过宽限期后不能再续期或出售，允许任何人撤销以免挂牌阻塞 close_expired_claim
+pub fn cancel_listing(ctx: Context<CancelListing>) -> Result<()> {
+    let listing = &ctx.accounts.listing;
+    let clock = Clock::get()?;
+    let claim_lapsed = ctx.accounts.claim.is_past_grace_period(clock.unix_timestamp);
+    let signer = ctx.accounts.signer.key();
+    let is_org_admin = ctx
+        .accounts
+        .organization
+        .as_ref()
+        .is_some_and(|organization| organization.key() == listing.seller && organization.is_admin(&signer));
+    require!(
+        signer == listing.seller
+            || is_org_admin
+            || listing.is_expired(clock.unix_timestamp)
+            || claim_lapsed,
+        MscError::InvalidAuthority
+    );
+    
+    let claim = &mut ctx.accounts.claim;
+    claim.is_listed = false;
+    
+    msg!("Listing cancelled:");
+    msg!("Claim: {}", claim.key());
+    msg!("Seller: {}", listing.seller);
+    
+    Ok(())
+}
+
+// 购买挂牌确权：扣除协议手续费后支付给卖家，同一交易内转移所有权
+// 协议手续费划入对应币种服务配置登记的金库 (见 fee_service_id)
+pub fn purchase_listing(ctx: Context<PurchaseListing>) -> Result<()> {
+    let clock = Clock::get()?;
+    let listing = &ctx.accounts.listing;
+    require!(!listing.is_expired(clock.unix_timestamp), MscError::ListingExpired);
+    
+    let buyer_key = ctx.accounts.buyer.key();
+    require!(
+        listing.allowed_buyers.is_empty() || listing.allowed_buyers.contains(&buyer_key),
+        MscError::BuyerNotAllowed
+    );
+    require!(buyer_key != listing.seller, MscError::InvalidRecipient);
+    
+    let claim = &ctx.accounts.claim;
+    require!(claim.is_active, MscError::ClaimNotFound);
+    require!(claim.is_listed, MscError::ClaimNotFound);
+    require!(claim.owner == listing.seller, MscError::InvalidAccountOwner);
+    require!(!claim.is_disputed, MscError::ClaimDisputed);
+    require!(!claim.is_expired(clock.unix_timestamp), MscError::ClaimExpired);
+    // 组织挂牌的售价须划入组织金库
+    if let Some(seller_treasury) = listing.seller_treasury {
+        require!(
+            ctx.accounts.seller_token_account.key() == seller_treasury,
+            MscError::InvalidRecipient
+        );
+    }
+    
+    let treasury_token_account = &ctx.accounts.treasury_token_account;
+    require!(
+        treasury_token_account.key() == ctx.accounts.service_config.treasury,
+        MscError::InvalidTreasury
+    );
+    require!(treasury_token_account.mint == listing.payment_mint, MscError::InvalidMint);
+    
+    let buyer_token_account = &ctx.accounts.buyer_token_account;
+    require!(buyer_token_account.amount >= listing.price, MscError::InsufficientBalance);
+    
+    let price = listing.price;
+    let protocol_fee = price
+        .checked_mul(MARKETPLACE_FEE_BPS)
+        .ok_or(MscError::MathOverflow)?
+        .checked_div(10_000)
+        .ok_or(MscError::DivisionByZero)?;
+    let seller_proceeds = price.checked_sub(protocol_fee).ok_or(MscError::MathUnderflow)?;
+    
+    let buyer = ctx.accounts.buyer.to_account_info();
+    if protocol_fee > 0 {
+        charge_service_fee(
+            &ctx.accounts.token_program,
+            buyer_token_account,
+            treasury_token_account,
+            buyer.clone(),
+            protocol_fee,
+        )?;
+    }
+    if seller_proceeds > 0 {
+        charge_service_fee(
+            &ctx.accounts.token_program,
+            buyer_token_account,
+            &ctx.accounts.seller_token_account,
+            buyer,
+            seller_proceeds,
+        )?;
+    }
+    
+    let seller = listing.seller;
+    let claim = &mut ctx.accounts.claim;
+    claim.is_listed = false;
+    transfer_indexed_claim(
+        claim,
+        buyer_key,
+        ctx.accounts.from_owner_index.as_deref_mut(),
+        Some(&mut ctx.accounts.to_owner_index_head),
+        Some(&mut ctx.accounts.to_owner_index),
+    )?;
+    
+    msg!("Listing purchased:");
+    msg!("Claim: {}", claim.key());
+    msg!("From: {}", seller);
+    msg!("To: {}", buyer_key);
+    msg!("Price: {}", price);
+    msg!("Protocol Fee: {}", protocol_fee);
+    
+    Ok(())
+}
+
+// Account 结构定义
+
+#[derive(Accounts)]
+pub struct ListClaim<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = Listing::LEN,
+        seeds = [b"listing", claim.key().as_ref()],
+        bump
+    )]
+    pub listing: Account<'info, Listing>,
+    
+    #[account(mut)]
+    pub claim: Account<'info, OwnershipClaim>,
+    
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Box<Account<'info, MscTokenConfig>>,
+    
+    // USDC 计价时传入
+    #[account(
+        seeds = [b"exchange_pool"],
+        bump
+    )]
+    pub exchange_pool: Option<Box<Account<'info, ExchangePool>>>,
+    
+    // 组织确权时传入，签名者须为组织管理员
+    #[account(
+        seeds = [b"organization", organization.name.as_bytes()],
+        bump
+    )]
+    pub organization: Option<Box<Account<'info, Organization>>>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelListing<'info> {
+    #[account(
+        mut,
+        close = seller,
+        seeds = [b"listing", claim.key().as_ref()],
+        bump
+    )]
+    pub listing: Account<'info, Listing>,
+    
+    #[account(mut, address = listing.claim)]
+    pub claim: Account<'info, OwnershipClaim>,
+    
+    /// CHECK: 挂牌租金退还给卖家
+    #[account(mut, address = listing.seller @ MscError::InvalidRecipient)]
+    pub seller: UncheckedAccount<'info>,
+    
+    // 组织挂牌时传入，组织管理员可撤销
+    #[account(
+        seeds = [b"organization", organization.name.as_bytes()],
+        bump
+    )]
+    pub organization: Option<Box<Account<'info, Organization>>>,
+    
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PurchaseListing<'info> {
+    #[account(
+        mut,
+        close = seller,
+        seeds = [b"listing", claim.key().as_ref()],
+        bump
+    )]
+    pub listing: Box<Account<'info, Listing>>,
+    
+    #[account(mut, address = listing.claim)]
+    pub claim: Box<Account<'info, OwnershipClaim>>,
+    
+    // 协议金库以挂牌币种对应的服务配置登记的金库为准
+    #[account(
+        seeds = [b"service_config", [fee_service_id(listing.currency)].as_ref()],
+        bump
+    )]
+    pub service_config: Box<Account<'info, ServiceConfig>>,
+    
+    #[account(
+        mut,
+        constraint = buyer_token_account.mint == listing.payment_mint @ MscError::InvalidMint,
+        constraint = buyer_token_account.owner == buyer.key() @ MscError::InvalidAccountOwner
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = seller_token_account.mint == listing.payment_mint @ MscError::InvalidMint,
+        constraint = seller_token_account.owner == listing.seller @ MscError::InvalidRecipient
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: 挂牌租金退还给卖家
+    #[account(mut, address = listing.seller @ MscError::InvalidRecipient)]
+    pub seller: UncheckedAccount<'info>,
+    
+    // 确权已建索引时传入卖家索引页
+    #[account(mut)]
+    pub from_owner_index: Option<Account<'info, OwnerIndexPage>>,
+    
+    // 买家的索引头与当前索引页 (首次使用时创建)
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = OwnerIndexHead::LEN,
+        seeds = [b"owner_index_head", buyer.key().as_ref()],
+        bump
+    )]
+    pub to_owner_index_head: Box<Account<'info, OwnerIndexHead>>,
+    
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = OwnerIndexPage::LEN,
+        seeds = [b"owner_index", buyer.key().as_ref(), to_owner_index_head.current_page.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub to_owner_index: Box<Account<'info, OwnerIndexPage>>,
+    
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
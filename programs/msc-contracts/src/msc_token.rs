@@ -162,10 +162,16 @@ pub struct MintMsc<'info> {
     )]
     pub config: Account<'info, MscTokenConfig>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        address = config.mint @ MscError::InvalidMint
+    )]
     pub mint: Account<'info, Mint>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = to.mint == config.mint @ MscError::InvalidMint
+    )]
     pub to: Account<'info, TokenAccount>,
     
     #[account(mut)]
@@ -176,10 +182,23 @@ pub struct MintMsc<'info> {
 
 #[derive(Accounts)]
 pub struct TransferMsc<'info> {
-    #[account(mut)]
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Account<'info, MscTokenConfig>,
+    
+    #[account(
+        mut,
+        constraint = from.mint == config.mint @ MscError::InvalidMint,
+        constraint = from.owner == authority.key() @ MscError::InvalidAccountOwner
+    )]
     pub from: Account<'info, TokenAccount>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = to.mint == config.mint @ MscError::InvalidMint
+    )]
     pub to: Account<'info, TokenAccount>,
     
     pub authority: Signer<'info>,
@@ -195,10 +214,17 @@ pub struct BatchAirdrop<'info> {
     )]
     pub config: Account<'info, MscTokenConfig>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = from.mint == config.mint @ MscError::InvalidMint,
+        constraint = from.owner == authority.key() @ MscError::InvalidAccountOwner
+    )]
     pub from: Account<'info, TokenAccount>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = to.mint == config.mint @ MscError::InvalidMint
+    )]
     pub to: Account<'info, TokenAccount>,
     
     #[account(mut)]
@@ -0,0 +1,313 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as instructions_sysvar;
+use anchor_lang::solana_program::sysvar::slot_hashes;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::state::*;
+use crate::error::*;
+use crate::ed25519::verify_preceding_ed25519;
+use crate::ownership::{validate_file_hash, resolve_claim_lineage, read_ledger_anchor, generate_transaction_id};
+use crate::subscription::apply_claim_credits;
+use crate::service::{
+    charge_service_fee, get_service_price, register_new_claim, validate_payment_accounts, ClaimRegistration,
+    NewClaim, SERVICE_CLAIM,
+};
+
+// 公证时间与链上时间允许的最大偏差 (秒)
+const NOTARY_ATTESTATION_WINDOW: u64 = 600;
+
+// 公证签名消息的域分隔标签，防止签名在其他场景被重放
+pub const NOTARY_MESSAGE_DOMAIN: &[u8] = b"MSC_NOTARIZED_CLAIM_V1";
+
+// 添加公证人 (仅限管理员)
+pub fn add_notary(
+    ctx: Context<AddNotary>,
+    notary: Pubkey,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    require!(config.is_initialized, MscError::AccountNotInitialized);
+    require!(ctx.accounts.authority.key() == config.authority, MscError::InvalidAuthority);
+    
+    let clock = Clock::get()?;
+    let notary_entry = &mut ctx.accounts.notary_entry;
+    notary_entry.notary = notary;
+    notary_entry.added_by = ctx.accounts.authority.key();
+    notary_entry.added_at = clock.unix_timestamp;
+    notary_entry.total_attestations = 0;
+    
+    msg!("Notary added: {}", notary);
+    Ok(())
+}
+
+// 移除公证人 (仅限管理员)
+pub fn remove_notary(
+    ctx: Context<RemoveNotary>,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    require!(config.is_initialized, MscError::AccountNotInitialized);
+    require!(ctx.accounts.authority.key() == config.authority, MscError::InvalidAuthority);
+    
+    msg!("Notary removed: {}", ctx.accounts.notary_entry.notary);
+    Ok(())
+}
+
+// 支付并创建公证确权记录
+// 交易中紧邻本指令之前须有 Ed25519 预编译指令，包含公证人对 (程序 ID, 域标签, owner, file_hash, attested_at) 的签名
+pub fn pay_and_create_notarized_claim(
+    ctx: Context<PayAndCreateNotarizedClaim>,
+    max_price: u64,
+    file_hash: String,
+    relation: ClaimRelation,
+    attested_at: i64,
+) -> Result<()> {
+    require!(validate_file_hash(&file_hash), MscError::InvalidFileHash);
+    
+    let lineage = resolve_claim_lineage(
+        ctx.accounts.parent_claim.as_ref(),
+        relation,
+        &ctx.accounts.user.key(),
+    )?;
+    
+    validate_payment_accounts(
+        &ctx.accounts.config,
+        &ctx.accounts.service_config,
+        &ctx.accounts.user_token_account,
+        &ctx.accounts.treasury_token_account,
+        ctx.accounts.user.key(),
+    )?;
+    let clock = Clock::get()?;
+    
+    // 传入订阅账户时扣减订阅额度，不再收取确权费用
+    let price = apply_claim_credits(
+        get_service_price(&ctx.accounts.service_config, SERVICE_CLAIM)?,
+        ctx.accounts.subscription.as_deref_mut().map(|s| &mut **s),
+        1,
+        clock.unix_timestamp,
+    )?;
+    require!(price <= max_price, MscError::PriceAboveMaximum);
+    
+    let user = ctx.accounts.user.key();
+    
+    // 验证公证签名
+    require!(
+        clock.unix_timestamp.abs_diff(attested_at) <= NOTARY_ATTESTATION_WINDOW,
+        MscError::AttestationExpired
+    );
+    let notary = ctx.accounts.notary_entry.notary;
+    let message = notarized_claim_message(ctx.program_id, &user, &file_hash, attested_at);
+    verify_preceding_ed25519(&ctx.accounts.instructions, &notary, &message)?;
+    
+    charge_service_fee(
+        &ctx.accounts.token_program,
+        &ctx.accounts.user_token_account,
+        &ctx.accounts.treasury_token_account,
+        ctx.accounts.user.to_account_info(),
+        price,
+    )?;
+    
+    let claim_sequence = register_new_claim(
+        ClaimRegistration {
+            claim: &mut ctx.accounts.claim,
+            payment_record: &mut ctx.accounts.payment_record,
+            global_state: &mut ctx.accounts.global_state,
+            owner_index_head: &mut ctx.accounts.owner_index_head,
+            owner_index: &mut ctx.accounts.owner_index,
+            stats: &mut **ctx.accounts.user_stats,
+        },
+        NewClaim {
+            owner: user,
+            payer: user,
+            file_hash: &file_hash,
+            lineage: &lineage,
+            amount: price,
+            certificate_amount: 0,
+            timestamp: clock.unix_timestamp,
+            ledger: read_ledger_anchor(clock.slot, &ctx.accounts.slot_hashes)?,
+            payment_sequence: None,
+        },
+    )?;
+    ctx.accounts.claim.notary = Some(notary);
+    
+    let notary_entry = &mut ctx.accounts.notary_entry;
+    notary_entry.total_attestations = notary_entry
+        .total_attestations
+        .checked_add(1)
+        .ok_or(MscError::MathOverflow)?;
+    
+    msg!("Notarized claim created:");
+    msg!("User: {}", user);
+    msg!("Amount: {} MSC", price);
+    msg!("File Hash: {}", file_hash);
+    msg!("Receipt: {}", generate_transaction_id(claim_sequence));
+    msg!("Notary: {}", notary);
+    msg!("Attested At: {}", attested_at);
+    
+    Ok(())
+}
+
+// Account 结构定义
+
+#[derive(Accounts)]
+#[instruction(notary: Pubkey)]
+pub struct AddNotary<'info> {
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Account<'info, MscTokenConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = Notary::LEN,
+        seeds = [b"notary", notary.as_ref()],
+        bump
+    )]
+    pub notary_entry: Account<'info, Notary>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveNotary<'info> {
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Account<'info, MscTokenConfig>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"notary", notary_entry.notary.as_ref()],
+        bump
+    )]
+    pub notary_entry: Account<'info, Notary>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(max_price: u64, file_hash: String)]
+pub struct PayAndCreateNotarizedClaim<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = PaymentRecord::LEN,
+        seeds = [b"payment", user.key().as_ref(), file_hash.as_bytes()],
+        bump
+    )]
+    pub payment_record: Box<Account<'info, PaymentRecord>>,
+    
+    #[account(
+        init,
+        payer = user,
+        space = OwnershipClaim::LEN,
+        seeds = [b"claim", user.key().as_ref(), file_hash.as_bytes()],
+        bump
+    )]
+    pub claim: Box<Account<'info, OwnershipClaim>>,
+    
+    #[account(
+        seeds = [b"service_config", [SERVICE_CLAIM].as_ref()],
+        bump
+    )]
+    pub service_config: Box<Account<'info, ServiceConfig>>,
+    
+    #[account(
+        mut,
+        seeds = [b"global_state"],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+    
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserStats::LEN,
+        seeds = [b"user_stats", user.key().as_ref()],
+        bump
+    )]
+    pub user_stats: Box<Account<'info, UserStats>>,
+    
+    // 所有者索引头与当前索引页 (首次使用时创建，无需事先初始化)
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = OwnerIndexHead::LEN,
+        seeds = [b"owner_index_head", user.key().as_ref()],
+        bump
+    )]
+    pub owner_index_head: Box<Account<'info, OwnerIndexHead>>,
+    
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = OwnerIndexPage::LEN,
+        seeds = [b"owner_index", user.key().as_ref(), owner_index_head.current_page.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub owner_index: Box<Account<'info, OwnerIndexPage>>,
+    
+    /// CHECK: SlotHashes sysvar，读取最新条目锚定确权
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+    
+    // 可选：以订阅额度抵扣确权费用
+    #[account(
+        mut,
+        seeds = [b"subscription", user.key().as_ref()],
+        bump
+    )]
+    pub subscription: Option<Box<Account<'info, Subscription>>>,
+    
+    pub parent_claim: Option<Account<'info, OwnershipClaim>>,
+    
+    #[account(
+        mut,
+        seeds = [b"notary", notary_entry.notary.as_ref()],
+        bump
+    )]
+    pub notary_entry: Account<'info, Notary>,
+    
+    /// CHECK: Instructions sysvar, used to read the Ed25519 precompile instruction
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Box<Account<'info, MscTokenConfig>>,
+    
+    #[account(mut)]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+// 辅助函数：构造公证签名消息 program_id(32) || domain || owner(32) || file_hash || attested_at(i64 LE)
+pub fn notarized_claim_message(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    file_hash: &str,
+    attested_at: i64,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(32 + NOTARY_MESSAGE_DOMAIN.len() + 32 + file_hash.len() + 8);
+    message.extend_from_slice(program_id.as_ref());
+    message.extend_from_slice(NOTARY_MESSAGE_DOMAIN);
+    message.extend_from_slice(owner.as_ref());
+    message.extend_from_slice(file_hash.as_bytes());
+    message.extend_from_slice(&attested_at.to_le_bytes());
+    message
+}
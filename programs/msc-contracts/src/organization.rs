@@ -0,0 +1,524 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::slot_hashes;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use crate::state::*;
+use crate::error::*;
+use crate::ownership::{validate_file_hash, resolve_claim_lineage, read_ledger_anchor, generate_transaction_id};
+use crate::subscription::apply_claim_credits;
+use crate::service::{
+    charge_service_fee, get_service_price, register_new_claim, validate_payment_accounts, ClaimRegistration,
+    NewClaim, SERVICE_CLAIM,
+};
+
+// 创建组织，创建者成为首位管理员，同时创建组织金库与组织统计
+pub fn create_organization(
+    ctx: Context<CreateOrganization>,
+    name: String,
+) -> Result<()> {
+    require!(
+        !name.is_empty() && name.len() <= Organization::MAX_NAME_LEN,
+        MscError::OrganizationNameTooLong
+    );
+    
+    let clock = Clock::get()?;
+    let creator = ctx.accounts.creator.key();
+    
+    let organization = &mut ctx.accounts.organization;
+    organization.name = name.clone();
+    organization.creator = creator;
+    organization.treasury = ctx.accounts.org_treasury.key();
+    organization.admins = vec![creator];
+    organization.member_count = 0;
+    organization.created_at = clock.unix_timestamp;
+    
+    let org_stats = &mut ctx.accounts.org_stats;
+    org_stats.organization = organization.key();
+    org_stats.total_claims = 0;
+    org_stats.total_payments = 0;
+    org_stats.last_activity = clock.unix_timestamp;
+    
+    msg!("Organization created:");
+    msg!("Name: {}", name);
+    msg!("Organization: {}", organization.key());
+    msg!("Treasury: {}", organization.treasury);
+    msg!("Admin: {}", creator);
+    
+    Ok(())
+}
+
+// 添加组织管理员 (仅限组织管理员)
+pub fn add_org_admin(
+    ctx: Context<UpdateOrgAdmins>,
+    admin: Pubkey,
+) -> Result<()> {
+    let organization = &mut ctx.accounts.organization;
+    require!(organization.is_admin(&ctx.accounts.admin.key()), MscError::InvalidAuthority);
+    require!(!organization.is_admin(&admin), MscError::OperationNotAllowed);
+    require!(
+        organization.admins.len() < Organization::MAX_ADMINS,
+        MscError::TooManyOrgAdmins
+    );
+    
+    organization.admins.push(admin);
+    
+    msg!("Organization admin added:");
+    msg!("Organization: {}", organization.key());
+    msg!("Admin: {}", admin);
+    
+    Ok(())
+}
+
+// 移除组织管理员 (仅限组织管理员，至少保留一名)
+pub fn remove_org_admin(
+    ctx: Context<UpdateOrgAdmins>,
+    admin: Pubkey,
+) -> Result<()> {
+    let organization = &mut ctx.accounts.organization;
+    require!(organization.is_admin(&ctx.accounts.admin.key()), MscError::InvalidAuthority);
+    require!(organization.is_admin(&admin), MscError::OperationNotAllowed);
+    require!(organization.admins.len() > 1, MscError::LastOrgAdmin);
+    
+    organization.admins.retain(|a| *a != admin);
+    
+    msg!("Organization admin removed:");
+    msg!("Organization: {}", organization.key());
+    msg!("Admin: {}", admin);
+    
+    Ok(())
+}
+
+// 添加组织成员 (仅限组织管理员)
+pub fn add_org_member(
+    ctx: Context<AddOrgMember>,
+    member: Pubkey,
+) -> Result<()> {
+    let admin = ctx.accounts.admin.key();
+    let organization = &mut ctx.accounts.organization;
+    require!(organization.is_admin(&admin), MscError::InvalidAuthority);
+    
+    organization.member_count = organization.member_count.checked_add(1).ok_or(MscError::MathOverflow)?;
+    
+    let org_member = &mut ctx.accounts.org_member;
+    org_member.organization = organization.key();
+    org_member.member = member;
+    org_member.added_by = admin;
+    org_member.added_at = Clock::get()?.unix_timestamp;
+    
+    msg!("Organization member added:");
+    msg!("Organization: {}", organization.key());
+    msg!("Member: {}", member);
+    
+    Ok(())
+}
+
+// 移除组织成员 (仅限组织管理员)，成员账户租金退还操作的管理员
+pub fn remove_org_member(ctx: Context<RemoveOrgMember>) -> Result<()> {
+    let organization = &mut ctx.accounts.organization;
+    require!(organization.is_admin(&ctx.accounts.admin.key()), MscError::InvalidAuthority);
+    
+    organization.member_count = organization.member_count.checked_sub(1).ok_or(MscError::MathUnderflow)?;
+    
+    msg!("Organization member removed:");
+    msg!("Organization: {}", organization.key());
+    msg!("Member: {}", ctx.accounts.org_member.member);
+    
+    Ok(())
+}
+
+// 从组织金库提取 MSC (仅限组织管理员)
+pub fn withdraw_org_treasury(
+    ctx: Context<WithdrawOrgTreasury>,
+    amount: u64,
+) -> Result<()> {
+    let organization = &ctx.accounts.organization;
+    require!(organization.is_admin(&ctx.accounts.admin.key()), MscError::InvalidAuthority);
+    require!(ctx.accounts.org_treasury.amount >= amount, MscError::InsufficientBalance);
+    
+    transfer_from_org_treasury(
+        &ctx.accounts.token_program,
+        &ctx.accounts.org_treasury,
+        &ctx.accounts.destination,
+        organization,
+        ctx.bumps.organization,
+        amount,
+    )?;
+    
+    msg!("Organization treasury withdrawn:");
+    msg!("Organization: {}", organization.key());
+    msg!("Amount: {} MSC", amount);
+    msg!("To: {}", ctx.accounts.destination.key());
+    
+    Ok(())
+}
+
+// 以组织为所有者支付并创建确权记录 (组织管理员或成员发起)
+// payer_token_account 为组织金库时由组织 PDA 签名扣费，否则由发起人自付
+pub fn pay_and_create_org_claim(
+    ctx: Context<PayAndCreateOrgClaim>,
+    max_price: u64,
+    file_hash: String,
+    relation: ClaimRelation,
+) -> Result<()> {
+    require!(validate_file_hash(&file_hash), MscError::InvalidFileHash);
+    
+    let organization = &ctx.accounts.organization;
+    let org_key = organization.key();
+    let member = ctx.accounts.member.key();
+    require!(
+        organization.is_admin(&member) || ctx.accounts.org_member.is_some(),
+        MscError::NotOrgMember
+    );
+    
+    let lineage = resolve_claim_lineage(ctx.accounts.parent_claim.as_ref(), relation, &org_key)?;
+    
+    let pays_from_treasury = ctx.accounts.payer_token_account.key() == organization.treasury;
+    let payer = if pays_from_treasury { org_key } else { member };
+    validate_payment_accounts(
+        &ctx.accounts.config,
+        &ctx.accounts.service_config,
+        &ctx.accounts.payer_token_account,
+        &ctx.accounts.treasury_token_account,
+        payer,
+    )?;
+    let clock = Clock::get()?;
+    
+    // 传入发起人的订阅账户时扣减其订阅额度，不再收取确权费用
+    let price = apply_claim_credits(
+        get_service_price(&ctx.accounts.service_config, SERVICE_CLAIM)?,
+        ctx.accounts.subscription.as_deref_mut().map(|s| &mut **s),
+        1,
+        clock.unix_timestamp,
+    )?;
+    require!(price <= max_price, MscError::PriceAboveMaximum);
+    
+    if pays_from_treasury {
+        require!(ctx.accounts.payer_token_account.amount >= price, MscError::InsufficientBalance);
+        transfer_from_org_treasury(
+            &ctx.accounts.token_program,
+            &ctx.accounts.payer_token_account,
+            &ctx.accounts.treasury_token_account,
+            organization,
+            ctx.bumps.organization,
+            price,
+        )?;
+    } else {
+        charge_service_fee(
+            &ctx.accounts.token_program,
+            &ctx.accounts.payer_token_account,
+            &ctx.accounts.treasury_token_account,
+            ctx.accounts.member.to_account_info(),
+            price,
+        )?;
+    }
+    
+    let claim_sequence = register_new_claim(
+        ClaimRegistration {
+            claim: &mut ctx.accounts.claim,
+            payment_record: &mut ctx.accounts.payment_record,
+            global_state: &mut ctx.accounts.global_state,
+            owner_index_head: &mut ctx.accounts.owner_index_head,
+            owner_index: &mut ctx.accounts.owner_index,
+            stats: &mut *ctx.accounts.org_stats,
+        },
+        NewClaim {
+            owner: org_key,
+            payer,
+            file_hash: &file_hash,
+            lineage: &lineage,
+            amount: price,
+            certificate_amount: 0,
+            timestamp: clock.unix_timestamp,
+            ledger: read_ledger_anchor(clock.slot, &ctx.accounts.slot_hashes)?,
+            payment_sequence: None,
+        },
+    )?;
+    
+    msg!("Organization claim created:");
+    msg!("Organization: {}", org_key);
+    msg!("Registered By: {}", member);
+    msg!("Paid By: {}", payer);
+    msg!("Amount: {} MSC", price);
+    msg!("File Hash: {}", file_hash);
+    msg!("Receipt: {}", generate_transaction_id(claim_sequence));
+    msg!("Timestamp: {}", clock.unix_timestamp);
+    
+    Ok(())
+}
+
+// 辅助函数：验证签名者有权管理确权记录
+// 组织确权的所有者为组织 PDA，由组织管理员签名并传入组织账户代为操作
+pub fn require_claim_authority(
+    claim: &OwnershipClaim,
+    signer: &Pubkey,
+    organization: Option<&Account<Organization>>,
+) -> Result<()> {
+    if claim.owner == *signer {
+        return Ok(());
+    }
+    let organization = organization.ok_or(MscError::InvalidAccountOwner)?;
+    require!(organization.key() == claim.owner, MscError::InvalidAccountOwner);
+    require!(organization.is_admin(signer), MscError::InvalidAuthority);
+    Ok(())
+}
+
+// 辅助函数：由组织 PDA 签名从组织金库转出
+fn transfer_from_org_treasury<'info>(
+    token_program: &Program<'info, Token>,
+    org_treasury: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    organization: &Account<'info, Organization>,
+    bump: u8,
+    amount: u64,
+) -> Result<()> {
+    let seeds: &[&[u8]] = &[b"organization", organization.name.as_bytes(), &[bump]];
+    let signer = &[seeds];
+    
+    let cpi_accounts = Transfer {
+        from: org_treasury.to_account_info(),
+        to: to.to_account_info(),
+        authority: organization.to_account_info(),
+    };
+    token::transfer(
+        CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer),
+        amount,
+    )
+}
+
+// Account 结构定义
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreateOrganization<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = Organization::LEN,
+        seeds = [b"organization", name.as_bytes()],
+        bump
+    )]
+    pub organization: Box<Account<'info, Organization>>,
+    
+    #[account(
+        init,
+        payer = creator,
+        space = OrgStats::LEN,
+        seeds = [b"org_stats", organization.key().as_ref()],
+        bump
+    )]
+    pub org_stats: Account<'info, OrgStats>,
+    
+    #[account(
+        init,
+        payer = creator,
+        seeds = [b"org_treasury", organization.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = organization
+    )]
+    pub org_treasury: Account<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Box<Account<'info, MscTokenConfig>>,
+    
+    #[account(address = config.mint @ MscError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateOrgAdmins<'info> {
+    #[account(
+        mut,
+        seeds = [b"organization", organization.name.as_bytes()],
+        bump
+    )]
+    pub organization: Account<'info, Organization>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(member: Pubkey)]
+pub struct AddOrgMember<'info> {
+    #[account(
+        mut,
+        seeds = [b"organization", organization.name.as_bytes()],
+        bump
+    )]
+    pub organization: Account<'info, Organization>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = OrgMember::LEN,
+        seeds = [b"org_member", organization.key().as_ref(), member.as_ref()],
+        bump
+    )]
+    pub org_member: Account<'info, OrgMember>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveOrgMember<'info> {
+    #[account(
+        mut,
+        seeds = [b"organization", organization.name.as_bytes()],
+        bump
+    )]
+    pub organization: Account<'info, Organization>,
+    
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"org_member", organization.key().as_ref(), org_member.member.as_ref()],
+        bump
+    )]
+    pub org_member: Account<'info, OrgMember>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawOrgTreasury<'info> {
+    #[account(
+        seeds = [b"organization", organization.name.as_bytes()],
+        bump
+    )]
+    pub organization: Account<'info, Organization>,
+    
+    #[account(
+        mut,
+        address = organization.treasury @ MscError::InvalidTreasury
+    )]
+    pub org_treasury: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = destination.mint == org_treasury.mint @ MscError::InvalidMint
+    )]
+    pub destination: Account<'info, TokenAccount>,
+    
+    pub admin: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(max_price: u64, file_hash: String)]
+pub struct PayAndCreateOrgClaim<'info> {
+    #[account(
+        init,
+        payer = member,
+        space = PaymentRecord::LEN,
+        seeds = [b"payment", organization.key().as_ref(), file_hash.as_bytes()],
+        bump
+    )]
+    pub payment_record: Box<Account<'info, PaymentRecord>>,
+    
+    #[account(
+        init,
+        payer = member,
+        space = OwnershipClaim::LEN,
+        seeds = [b"claim", organization.key().as_ref(), file_hash.as_bytes()],
+        bump
+    )]
+    pub claim: Box<Account<'info, OwnershipClaim>>,
+    
+    #[account(
+        seeds = [b"organization", organization.name.as_bytes()],
+        bump
+    )]
+    pub organization: Box<Account<'info, Organization>>,
+    
+    // 非组织管理员时传入
+    #[account(
+        seeds = [b"org_member", organization.key().as_ref(), member.key().as_ref()],
+        bump
+    )]
+    pub org_member: Option<Account<'info, OrgMember>>,
+    
+    #[account(
+        mut,
+        seeds = [b"org_stats", organization.key().as_ref()],
+        bump
+    )]
+    pub org_stats: Account<'info, OrgStats>,
+    
+    // 所有者索引头与当前索引页 (首次使用时创建，无需事先初始化)
+    #[account(
+        init_if_needed,
+        payer = member,
+        space = OwnerIndexHead::LEN,
+        seeds = [b"owner_index_head", organization.key().as_ref()],
+        bump
+    )]
+    pub owner_index_head: Box<Account<'info, OwnerIndexHead>>,
+    
+    #[account(
+        init_if_needed,
+        payer = member,
+        space = OwnerIndexPage::LEN,
+        seeds = [b"owner_index", organization.key().as_ref(), owner_index_head.current_page.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub owner_index: Box<Account<'info, OwnerIndexPage>>,
+    
+    /// CHECK: SlotHashes sysvar，读取最新条目锚定确权
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"service_config", [SERVICE_CLAIM].as_ref()],
+        bump
+    )]
+    pub service_config: Box<Account<'info, ServiceConfig>>,
+    
+    #[account(
+        mut,
+        seeds = [b"global_state"],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+    
+    // 可选：以订阅额度抵扣确权费用
+    #[account(
+        mut,
+        seeds = [b"subscription", member.key().as_ref()],
+        bump
+    )]
+    pub subscription: Option<Box<Account<'info, Subscription>>>,
+    
+    // 新版本或衍生作品引用的父确权记录 (原创作品不传)
+    pub parent_claim: Option<Account<'info, OwnershipClaim>>,
+    
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Box<Account<'info, MscTokenConfig>>,
+    
+    // 组织金库或发起人自己的 MSC 代币账户
+    #[account(mut)]
+    pub payer_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub member: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+
+// 查询所有者索引页 (无需权限，结果通过 return data 返回)
+pub fn get_owner_index_page(ctx: Context<GetOwnerIndexPage>) -> Result<OwnerIndexView> {
+    let index_page = &ctx.accounts.owner_index;
+    
+    Ok(OwnerIndexView {
+        owner: index_page.owner,
+        page: index_page.page,
+        claims: index_page.claims.clone(),
+    })
+}
+
+// 辅助函数：将确权登记到其当前所有者的当前索引页
+// 索引头与索引页均为 init_if_needed，首次写入时在此补全所有者与页号
+pub fn index_claim(
+    index_head: &mut OwnerIndexHead,
+    index_page: &mut OwnerIndexPage,
+    claim: &mut Account<OwnershipClaim>,
+) -> Result<()> {
+    if index_head.owner == Pubkey::default() {
+        index_head.owner = claim.owner;
+    }
+    if index_page.owner == Pubkey::default() {
+        index_page.owner = claim.owner;
+        index_page.page = index_head.current_page;
+    }
+    require!(
+        index_head.owner == claim.owner
+            && index_page.owner == claim.owner
+            && index_page.page == index_head.current_page,
+        MscError::OwnerIndexMismatch
+    );
+    require!(
+        index_page.claims.len() < OwnerIndexPage::MAX_CLAIMS,
+        MscError::OwnerIndexPageFull
+    );
+    
+    index_page.claims.push(claim.key());
+    claim.index_page = Some(index_page.page);
+    
+    // 当前页写满后，下一条确权写入新页
+    if index_page.claims.len() == OwnerIndexPage::MAX_CLAIMS {
+        index_head.current_page = index_head
+            .current_page
+            .checked_add(1)
+            .ok_or(MscError::MathOverflow)?;
+    }
+    Ok(())
+}
+
+// 辅助函数：将确权从其当前所有者的索引页移除 (未建索引的确权无需传入索引页)
+pub fn unindex_claim(
+    index_page: Option<&mut OwnerIndexPage>,
+    claim: &mut Account<OwnershipClaim>,
+) -> Result<()> {
+    let Some(page) = claim.index_page else {
+        return Ok(());
+    };
+    
+    let index_page = index_page.ok_or(MscError::OwnerIndexRequired)?;
+    require!(
+        index_page.owner == claim.owner && index_page.page == page,
+        MscError::OwnerIndexMismatch
+    );
+    let claim_key = claim.key();
+    let position = index_page
+        .claims
+        .iter()
+        .position(|c| *c == claim_key)
+        .ok_or(MscError::OwnerIndexMismatch)?;
+    
+    index_page.claims.swap_remove(position);
+    claim.index_page = None;
+    Ok(())
+}
+
+// 辅助函数：转移确权所有权并同步新旧所有者的索引页
+// 新所有者的索引头与当前页由调用方以 init_if_needed 传入，无需新所有者事先创建
+pub fn transfer_indexed_claim(
+    claim: &mut Account<OwnershipClaim>,
+    new_owner: Pubkey,
+    from_index: Option<&mut OwnerIndexPage>,
+    to_index_head: Option<&mut OwnerIndexHead>,
+    to_index: Option<&mut OwnerIndexPage>,
+) -> Result<()> {
+    require!(new_owner != claim.owner, MscError::InvalidRecipient);
+    let to_index_head = to_index_head.ok_or(MscError::OwnerIndexRequired)?;
+    let to_index = to_index.ok_or(MscError::OwnerIndexRequired)?;
+    unindex_claim(from_index, claim)?;
+    claim.owner = new_owner;
+    index_claim(to_index_head, to_index, claim)
+}
+
+// Account 结构定义
+
+#[derive(Accounts)]
+pub struct GetOwnerIndexPage<'info> {
+    pub owner_index: Account<'info, OwnerIndexPage>,
+}
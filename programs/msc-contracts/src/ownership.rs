@@ -1,6 +1,8 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::error::*;
+use crate::owner_index::transfer_indexed_claim;
+use crate::organization::require_claim_authority;
 
 
 
@@ -13,12 +15,168 @@ pub fn get_claim(
     require!(claim.is_active, MscError::ClaimNotFound);
     require!(claim.owner == ctx.accounts.owner.key(), MscError::InvalidAccountOwner);
     
+    let clock = Clock::get()?;
+    
     msg!("Claim found:");
     msg!("Owner: {}", claim.owner);
     msg!("File Hash: {}", claim.file_hash);
     msg!("Timestamp: {}", claim.timestamp);
+    msg!("Slot: {}", claim.slot);
     msg!("Transaction ID: {}", claim.transaction_id);
-    msg!("Status: Active");
+    if claim.is_disputed {
+        msg!("Status: Disputed");
+    } else if claim.is_expired(clock.unix_timestamp) {
+        msg!("Status: Expired");
+    } else {
+        msg!("Status: Active");
+    }
+    msg!("Expires At: {}", claim.expires_at);
+    
+    Ok(())
+}
+
+// 验证确权记录 (无需权限，结果通过 return data 返回，可供其他程序 CPI 调用)
+pub fn verify_claim(
+    ctx: Context<VerifyClaim>,
+    file_hash: String,
+) -> Result<ClaimVerification> {
+    let claim = &ctx.accounts.claim;
+    require!(claim.file_hash == file_hash, MscError::FileHashMismatch);
+    
+    let clock = Clock::get()?;
+    let verification = ClaimVerification {
+        claim: claim.key(),
+        owner: claim.owner,
+        timestamp: claim.timestamp,
+        status: claim.status(clock.unix_timestamp),
+        algorithm: claim.hash_algorithm,
+        expires_at: claim.expires_at,
+        slot: claim.slot,
+        recent_slot: claim.recent_slot,
+        recent_slot_hash: claim.recent_slot_hash,
+        is_co_owned: claim.is_co_owned,
+    };
+    
+    msg!("Claim verified:");
+    msg!("Claim: {}", verification.claim);
+    msg!("Owner: {}", verification.owner);
+    msg!("Timestamp: {}", verification.timestamp);
+    msg!("Slot: {}", verification.slot);
+    
+    Ok(verification)
+}
+
+// 设置确权元数据 (仅限单一所有者或组织管理员，首次创建)
+pub fn set_claim_metadata(
+    ctx: Context<SetClaimMetadata>,
+    title: String,
+    content_type: String,
+    uri: String,
+    license: String,
+) -> Result<()> {
+    let claim = &ctx.accounts.claim;
+    require!(claim.is_active, MscError::ClaimNotFound);
+    require_claim_authority(claim, &ctx.accounts.owner.key(), ctx.accounts.organization.as_deref())?;
+    require!(!claim.is_co_owned, MscError::ClaimCoOwned);
+    validate_claim_metadata(&title, &content_type, &uri, &license)?;
+    
+    let clock = Clock::get()?;
+    let metadata = &mut ctx.accounts.metadata;
+    metadata.claim = claim.key();
+    metadata.version = 1;
+    metadata.title = title;
+    metadata.content_type = content_type;
+    metadata.uri = uri;
+    metadata.license = license;
+    metadata.updated_at = clock.unix_timestamp;
+    
+    msg!("Claim metadata set:");
+    msg!("Claim: {}", metadata.claim);
+    msg!("Title: {}", metadata.title);
+    msg!("Version: {}", metadata.version);
+    
+    Ok(())
+}
+
+// 更新确权元数据 (仅限单一所有者或组织管理员，版本号递增)
+pub fn update_claim_metadata(
+    ctx: Context<UpdateClaimMetadata>,
+    title: String,
+    content_type: String,
+    uri: String,
+    license: String,
+) -> Result<()> {
+    let claim = &ctx.accounts.claim;
+    require!(claim.is_active, MscError::ClaimNotFound);
+    require_claim_authority(claim, &ctx.accounts.owner.key(), ctx.accounts.organization.as_deref())?;
+    require!(!claim.is_co_owned, MscError::ClaimCoOwned);
+    validate_claim_metadata(&title, &content_type, &uri, &license)?;
+    
+    let clock = Clock::get()?;
+    let metadata = &mut ctx.accounts.metadata;
+    metadata.version = metadata.version.checked_add(1).ok_or(MscError::MathOverflow)?;
+    metadata.title = title;
+    metadata.content_type = content_type;
+    metadata.uri = uri;
+    metadata.license = license;
+    metadata.updated_at = clock.unix_timestamp;
+    
+    msg!("Claim metadata updated:");
+    msg!("Claim: {}", metadata.claim);
+    msg!("Title: {}", metadata.title);
+    msg!("Version: {}", metadata.version);
+    
+    Ok(())
+}
+
+// 转让确权记录 (仅限单一所有者，组织确权由组织管理员发起)
+pub fn transfer_claim(
+    ctx: Context<TransferClaim>,
+    new_owner: Pubkey,
+) -> Result<()> {
+    let claim = &mut ctx.accounts.claim;
+    require!(claim.is_active, MscError::ClaimNotFound);
+    require_claim_authority(claim, &ctx.accounts.owner.key(), ctx.accounts.organization.as_deref())?;
+    require!(!claim.is_co_owned, MscError::ClaimCoOwned);
+    require!(claim.certificate_mint.is_none(), MscError::ClaimHasCertificate);
+    require!(new_owner != Pubkey::default(), MscError::InvalidRecipient);
+    require!(!claim.is_expired(Clock::get()?.unix_timestamp), MscError::ClaimExpired);
+    require!(!claim.is_disputed, MscError::ClaimDisputed);
+    require!(!claim.is_listed, MscError::ClaimListed);
+    
+    let old_owner = claim.owner;
+    transfer_indexed_claim(
+        claim,
+        new_owner,
+        ctx.accounts.from_owner_index.as_deref_mut(),
+        Some(&mut ctx.accounts.to_owner_index_head),
+        Some(&mut ctx.accounts.to_owner_index),
+    )?;
+    
+    msg!("Claim transferred:");
+    msg!("File Hash: {}", claim.file_hash);
+    msg!("From: {}", old_owner);
+    msg!("To: {}", new_owner);
+    
+    Ok(())
+}
+
+// 撤销确权记录 (仅限单一所有者或组织管理员)
+pub fn revoke_claim(
+    ctx: Context<RevokeClaim>,
+) -> Result<()> {
+    let claim = &mut ctx.accounts.claim;
+    require!(claim.is_active, MscError::ClaimNotFound);
+    require_claim_authority(claim, &ctx.accounts.owner.key(), ctx.accounts.organization.as_deref())?;
+    require!(!claim.is_co_owned, MscError::ClaimCoOwned);
+    require!(!claim.is_disputed, MscError::ClaimDisputed);
+    require!(!claim.is_listed, MscError::ClaimListed);
+    
+    claim.is_active = false;
+    
+    msg!("Claim revoked:");
+    msg!("Owner: {}", claim.owner);
+    msg!("File Hash: {}", claim.file_hash);
     
     Ok(())
 }
@@ -33,12 +191,258 @@ pub struct GetClaim<'info> {
     pub owner: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct VerifyClaim<'info> {
+    pub claim: Account<'info, OwnershipClaim>,
+}
+
+#[derive(Accounts)]
+#[instruction(new_owner: Pubkey)]
+pub struct TransferClaim<'info> {
+    #[account(mut)]
+    pub claim: Account<'info, OwnershipClaim>,
+    
+    // 确权已建索引时传入原所有者索引页
+    #[account(mut)]
+    pub from_owner_index: Option<Account<'info, OwnerIndexPage>>,
+    
+    // 新所有者的索引头与当前索引页 (首次使用时由转出方创建)
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = OwnerIndexHead::LEN,
+        seeds = [b"owner_index_head", new_owner.as_ref()],
+        bump
+    )]
+    pub to_owner_index_head: Box<Account<'info, OwnerIndexHead>>,
+    
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = OwnerIndexPage::LEN,
+        seeds = [b"owner_index", new_owner.as_ref(), to_owner_index_head.current_page.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub to_owner_index: Box<Account<'info, OwnerIndexPage>>,
+    
+    // 组织确权时传入，签名者须为组织管理员
+    #[account(
+        seeds = [b"organization", organization.name.as_bytes()],
+        bump
+    )]
+    pub organization: Option<Box<Account<'info, Organization>>>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeClaim<'info> {
+    #[account(mut)]
+    pub claim: Account<'info, OwnershipClaim>,
+    
+    // 组织确权时传入，签名者须为组织管理员
+    #[account(
+        seeds = [b"organization", organization.name.as_bytes()],
+        bump
+    )]
+    pub organization: Option<Box<Account<'info, Organization>>>,
+    
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(title: String, content_type: String, uri: String, license: String)]
+pub struct SetClaimMetadata<'info> {
+    pub claim: Account<'info, OwnershipClaim>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = ClaimMetadata::space(&title, &content_type, &uri, &license),
+        seeds = [b"claim_metadata", claim.key().as_ref()],
+        bump
+    )]
+    pub metadata: Account<'info, ClaimMetadata>,
+    
+    // 组织确权时传入，签名者须为组织管理员
+    #[account(
+        seeds = [b"organization", organization.name.as_bytes()],
+        bump
+    )]
+    pub organization: Option<Box<Account<'info, Organization>>>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(title: String, content_type: String, uri: String, license: String)]
+pub struct UpdateClaimMetadata<'info> {
+    pub claim: Account<'info, OwnershipClaim>,
+    
+    #[account(
+        mut,
+        seeds = [b"claim_metadata", claim.key().as_ref()],
+        bump,
+        realloc = ClaimMetadata::space(&title, &content_type, &uri, &license),
+        realloc::payer = owner,
+        realloc::zero = false,
+    )]
+    pub metadata: Account<'info, ClaimMetadata>,
+    
+    // 组织确权时传入，签名者须为组织管理员
+    #[account(
+        seeds = [b"organization", organization.name.as_bytes()],
+        bump
+    )]
+    pub organization: Option<Box<Account<'info, Organization>>>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
 // 辅助函数：验证文件哈希格式
 pub fn validate_file_hash(hash: &str) -> bool {
     hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit())
 }
 
-// 辅助函数：生成交易ID
-pub fn generate_transaction_id(owner: &Pubkey, timestamp: i64) -> String {
-    format!("{}-{}", owner.to_string()[..8].to_string(), timestamp)
+// 辅助函数：验证确权元数据字段
+pub fn validate_claim_metadata(
+    title: &str,
+    content_type: &str,
+    uri: &str,
+    license: &str,
+) -> Result<()> {
+    require!(title.len() <= ClaimMetadata::MAX_TITLE_LEN, MscError::MetadataFieldTooLong);
+    require!(content_type.len() <= ClaimMetadata::MAX_CONTENT_TYPE_LEN, MscError::MetadataFieldTooLong);
+    require!(uri.len() <= ClaimMetadata::MAX_URI_LEN, MscError::MetadataFieldTooLong);
+    require!(license.len() <= ClaimMetadata::MAX_LICENSE_LEN, MscError::MetadataFieldTooLong);
+    
+    // MIME 类型格式: type/subtype
+    let valid_content_type = match content_type.split_once('/') {
+        Some((kind, subtype)) => !kind.is_empty()
+            && !subtype.is_empty()
+            && content_type.chars().all(|c| c.is_ascii_graphic()),
+        None => false,
+    };
+    require!(valid_content_type, MscError::InvalidContentType);
+    
+    Ok(())
+}
+
+// 新确权记录的谱系信息
+pub struct ClaimLineage {
+    pub parent: Option<Pubkey>,
+    pub version: u32,
+    pub relation: ClaimRelation,
+}
+
+// 辅助函数：根据父记录确定新确权记录的父引用和版本号
+pub fn resolve_claim_lineage(
+    parent: Option<&Account<OwnershipClaim>>,
+    relation: ClaimRelation,
+    creator: &Pubkey,
+) -> Result<ClaimLineage> {
+    let (parent, version) = match (relation, parent) {
+        (ClaimRelation::Original, None) => (None, 1),
+        (ClaimRelation::Original, Some(_)) => return err!(MscError::UnexpectedParentClaim),
+        (_, None) => return err!(MscError::ParentClaimRequired),
+        (ClaimRelation::Revision, Some(parent)) => {
+            require!(parent.is_active, MscError::ClaimNotFound);
+            require!(parent.owner == *creator, MscError::RevisionNotByOwner);
+            let version = parent.version.checked_add(1).ok_or(MscError::MathOverflow)?;
+            (Some(parent.key()), version)
+        }
+        (ClaimRelation::Derivative, Some(parent)) => {
+            require!(parent.is_active, MscError::ClaimNotFound);
+            (Some(parent.key()), 1)
+        }
+    };
+    
+    Ok(ClaimLineage {
+        parent,
+        version,
+        relation,
+    })
+}
+
+// 辅助函数：写入新确权记录
+pub fn write_new_claim(
+    claim: &mut OwnershipClaim,
+    owner: Pubkey,
+    file_hash: String,
+    timestamp: i64,
+    lineage: &ClaimLineage,
+    sequence: u64,
+) {
+    claim.owner = owner;
+    claim.file_hash = file_hash;
+    claim.timestamp = timestamp;
+    claim.sequence = sequence;
+    claim.transaction_id = generate_transaction_id(sequence);
+    claim.is_active = true;
+    claim.is_co_owned = false;
+    claim.parent = lineage.parent;
+    claim.version = lineage.version;
+    claim.relation = lineage.relation;
+    claim.notary = None;
+    claim.hash_algorithm = HashAlgorithm::Sha256;
+    claim.certificate_mint = None;
+    claim.expires_at = timestamp.saturating_add(OwnershipClaim::TERM);
+    claim.is_disputed = false;
+    claim.is_listed = false;
+    claim.index_page = None;
+    claim.proposal_count = 0;
+    claim.payment_sequence = 0;
+}
+
+// 账本锚点：确权时间戳确定时的 slot，及同一交易中读取的 SlotHashes 最新条目
+#[derive(Clone, Copy)]
+pub struct LedgerAnchor {
+    pub slot: u64,
+    pub recent_slot: u64,
+    pub recent_slot_hash: [u8; 32],
+}
+
+// 辅助函数：在确权时间戳确定的交易中读取账本锚点
+// SlotHashes 最新条目证明该交易不早于 recent_slot，交易所在 slot 证明其不晚于 slot
+pub fn read_ledger_anchor(slot: u64, slot_hashes: &AccountInfo) -> Result<LedgerAnchor> {
+    // SlotHashes 布局：条目数 (u64 LE)，随后按 slot 降序排列的 (slot: u64 LE, hash: [u8; 32])
+    let data = slot_hashes.try_borrow_data()?;
+    require!(data.len() >= 8 + 8 + 32, MscError::SlotHashesUnavailable);
+    let mut entry_count = [0u8; 8];
+    entry_count.copy_from_slice(&data[0..8]);
+    require!(u64::from_le_bytes(entry_count) > 0, MscError::SlotHashesUnavailable);
+    
+    let mut recent_slot = [0u8; 8];
+    recent_slot.copy_from_slice(&data[8..16]);
+    let mut recent_slot_hash = [0u8; 32];
+    recent_slot_hash.copy_from_slice(&data[16..48]);
+    Ok(LedgerAnchor {
+        slot,
+        recent_slot: u64::from_le_bytes(recent_slot),
+        recent_slot_hash,
+    })
+}
+
+// 辅助函数：将确权锚定到账本位置
+// 先提交后揭示的确权使用提交交易中读取的锚点，三个字段始终来自同一交易，
+// 因此 recent_slot <= slot 恒成立，确权时间戳落在两者之间的账本区间内
+// 程序无法在链上读取自身交易签名，付款交易由 payment_sequence 关联的支付记录 (或承诺账户) 查得
+pub fn anchor_claim_to_ledger(claim: &mut OwnershipClaim, anchor: &LedgerAnchor) {
+    claim.slot = anchor.slot;
+    claim.recent_slot = anchor.recent_slot;
+    claim.recent_slot_hash = anchor.recent_slot_hash;
+}
+
+// 辅助函数：由全局回执序号生成交易ID
+pub fn generate_transaction_id(sequence: u64) -> String {
+    format!("MSC-{:012}", sequence)
 }
\ No newline at end of file
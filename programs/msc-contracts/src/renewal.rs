@@ -0,0 +1,181 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::state::*;
+use crate::error::*;
+use crate::owner_index::unindex_claim;
+use crate::organization::require_claim_authority;
+use crate::service::{
+    charge_service_fee, get_service_price, validate_payment_accounts, SERVICE_CLAIM_RENEWAL,
+};
+
+// 续期确权记录 (仅限单一所有者或组织管理员，到期后宽限期内仍可续期)
+// 新有效期从原截止时间顺延一个周期，保证有效期连续
+pub fn renew_claim(
+    ctx: Context<RenewClaim>,
+    max_price: u64,
+) -> Result<()> {
+    let claim = &ctx.accounts.claim;
+    require!(claim.is_active, MscError::ClaimNotFound);
+    require_claim_authority(claim, &ctx.accounts.owner.key(), ctx.accounts.organization.as_deref())?;
+    require!(!claim.is_co_owned, MscError::ClaimCoOwned);
+    
+    let clock = Clock::get()?;
+    require!(!claim.is_past_grace_period(clock.unix_timestamp), MscError::GracePeriodEnded);
+    
+    validate_payment_accounts(
+        &ctx.accounts.config,
+        &ctx.accounts.service_config,
+        &ctx.accounts.owner_token_account,
+        &ctx.accounts.treasury_token_account,
+        ctx.accounts.owner.key(),
+    )?;
+    let price = get_service_price(&ctx.accounts.service_config, SERVICE_CLAIM_RENEWAL)?;
+    require!(price <= max_price, MscError::PriceAboveMaximum);
+    
+    charge_service_fee(
+        &ctx.accounts.token_program,
+        &ctx.accounts.owner_token_account,
+        &ctx.accounts.treasury_token_account,
+        ctx.accounts.owner.to_account_info(),
+        price,
+    )?;
+    
+    let claim = &mut ctx.accounts.claim;
+    let old_expires_at = claim.expires_at;
+    claim.expires_at = old_expires_at
+        .checked_add(OwnershipClaim::TERM)
+        .ok_or(MscError::MathOverflow)?;
+    
+    msg!("Claim renewed:");
+    msg!("File Hash: {}", claim.file_hash);
+    msg!("Amount: {} MSC", price);
+    msg!("Old Expiry: {}", old_expires_at);
+    msg!("New Expiry: {}", claim.expires_at);
+    
+    Ok(())
+}
+
+// 关闭宽限期已过的确权记录 (无需权限)，租金退还原创建者，该文件哈希可重新确权
+// 重新确权复用同一确权 PDA，关联的元数据与共有人账户必须一并关闭
+pub fn close_expired_claim(
+    ctx: Context<CloseExpiredClaim>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let claim = &ctx.accounts.claim;
+    require!(claim.is_past_grace_period(clock.unix_timestamp), MscError::ClaimNotExpired);
+    require!(!claim.is_disputed, MscError::ClaimDisputed);
+    require!(!claim.is_listed, MscError::ClaimListed);
+    require!(
+        !claim.is_co_owned || ctx.accounts.co_owners.is_some(),
+        MscError::CoOwnersRequired
+    );
+    
+    let metadata_info = ctx.accounts.claim_metadata.to_account_info();
+    if metadata_info.owner == ctx.program_id && metadata_info.lamports() > 0 {
+        close_program_account(&metadata_info, &ctx.accounts.creator.to_account_info())?;
+    }
+    
+    let claim = &mut ctx.accounts.claim;
+    unindex_claim(ctx.accounts.owner_index.as_deref_mut(), claim)?;
+    
+    msg!("Expired claim closed:");
+    msg!("Claim: {}", claim.key());
+    msg!("File Hash: {}", claim.file_hash);
+    msg!("Expired At: {}", claim.expires_at);
+    
+    Ok(())
+}
+
+// 辅助函数：关闭本程序拥有的账户，租金转给接收方
+fn close_program_account<'info>(
+    account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    let lamports = account.lamports();
+    destination.add_lamports(lamports)?;
+    account.sub_lamports(lamports)?;
+    account.assign(&System::id());
+    account.resize(0)?;
+    Ok(())
+}
+
+// Account 结构定义
+
+#[derive(Accounts)]
+pub struct RenewClaim<'info> {
+    #[account(mut)]
+    pub claim: Account<'info, OwnershipClaim>,
+    
+    #[account(
+        seeds = [b"service_config", [SERVICE_CLAIM_RENEWAL].as_ref()],
+        bump
+    )]
+    pub service_config: Box<Account<'info, ServiceConfig>>,
+    
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Box<Account<'info, MscTokenConfig>>,
+    
+    #[account(mut)]
+    pub owner_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    
+    // 组织确权时传入，签名者须为组织管理员
+    #[account(
+        seeds = [b"organization", organization.name.as_bytes()],
+        bump
+    )]
+    pub organization: Option<Box<Account<'info, Organization>>>,
+    
+    pub owner: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CloseExpiredClaim<'info> {
+    #[account(
+        mut,
+        close = creator,
+        seeds = [b"claim", creator.key().as_ref(), claim.file_hash.as_bytes()],
+        bump
+    )]
+    pub claim: Account<'info, OwnershipClaim>,
+    
+    #[account(
+        mut,
+        close = creator,
+        seeds = [b"payment", creator.key().as_ref(), claim.file_hash.as_bytes()],
+        bump
+    )]
+    pub payment_record: Account<'info, PaymentRecord>,
+    
+    /// CHECK: 确权元数据 PDA，地址由种子校验；账户存在时一并关闭，避免阻塞重新确权后的元数据设置
+    #[account(
+        mut,
+        seeds = [b"claim_metadata", claim.key().as_ref()],
+        bump
+    )]
+    pub claim_metadata: UncheckedAccount<'info>,
+    
+    // 共有确权时必须传入，一并关闭
+    #[account(
+        mut,
+        close = creator,
+        seeds = [b"co_owners", claim.key().as_ref()],
+        bump
+    )]
+    pub co_owners: Option<Account<'info, ClaimCoOwners>>,
+    
+    // 确权已建索引时传入当前所有者索引页
+    #[account(mut)]
+    pub owner_index: Option<Account<'info, OwnerIndexPage>>,
+    
+    /// CHECK: 确权记录的原创建者 (PDA 种子)，接收退还的租金
+    #[account(mut)]
+    pub creator: UncheckedAccount<'info>,
+}
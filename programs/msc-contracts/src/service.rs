@@ -1,19 +1,43 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::sysvar::slot_hashes;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::Metadata;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
 use crate::state::*;
 use crate::error::*;
-use crate::ownership::validate_file_hash;
+use crate::ownership::{validate_file_hash, resolve_claim_lineage, write_new_claim, anchor_claim_to_ledger, read_ledger_anchor, generate_transaction_id, ClaimLineage, LedgerAnchor};
+use crate::certificate::mint_claim_certificate;
+use crate::owner_index::index_claim;
+use crate::subscription::apply_claim_credits;
+use crate::exchange::calculate_swap_input;
 // use crate::ownership;
 
-// 确权服务价格 (MSC tokens, 6 decimals)
-const CLAIM_PRICE: u64 = 1_000_000;      // 1 MSC
+// 服务类型 (ServiceConfig PDA: ["service_config", service_id])
+pub const SERVICE_CLAIM: u8 = 0;              // 确权
+pub const SERVICE_CLAIM_RENEWAL: u8 = 1;      // 确权续期
+pub const SERVICE_CERTIFICATE: u8 = 2;        // 确权证书
+pub const SERVICE_PRIORITY_TIMESTAMP: u8 = 3; // 优先时间戳
+pub const SERVICE_DISPUTE_FILING: u8 = 4;     // 争议申请
+pub const SERVICE_MARKETPLACE_USDC: u8 = 5;   // USDC 计价确权交易 (金库为 USDC 代币账户)
+const SERVICE_COUNT: u8 = 6;
+
+// 批量确权阶梯折扣 (最低文件数, 折扣基点)，按文件数从高到低匹配
+const BATCH_DISCOUNT_TIERS: [(u32, u64); 3] = [
+    (10_000, 8_000), // 10000 个及以上: 优惠 80%
+    (1_000, 5_000),  // 1000 个及以上: 优惠 50%
+    (100, 2_000),    // 100 个及以上: 优惠 20%
+];
+const MAX_BATCH_CLAIM_COUNT: u32 = 1_000_000;
+
+// 待处理支付超时后付款人可自行退款 (7 天)
+pub const PAYMENT_REFUND_TIMEOUT: i64 = 7 * 24 * 60 * 60;
 
 
 
 // Account 结构定义
 
 #[derive(Accounts)]
-#[instruction(amount: u64, file_hash: String)]
+#[instruction(max_price: u64, file_hash: String)]
 pub struct PayAndCreateClaim<'info> {
     #[account(
         init,
@@ -34,7 +58,20 @@ pub struct PayAndCreateClaim<'info> {
     pub claim: Account<'info, OwnershipClaim>,
     
     #[account(
-        init,
+        seeds = [b"service_config", [SERVICE_CLAIM].as_ref()],
+        bump
+    )]
+    pub service_config: Box<Account<'info, ServiceConfig>>,
+    
+    #[account(
+        mut,
+        seeds = [b"global_state"],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+    
+    #[account(
+        init_if_needed,
         payer = user,
         space = UserStats::LEN,
         seeds = [b"user_stats", user.key().as_ref()],
@@ -42,6 +79,46 @@ pub struct PayAndCreateClaim<'info> {
     )]
     pub user_stats: Account<'info, UserStats>,
     
+    // 所有者索引头与当前索引页 (首次使用时创建，无需事先初始化)
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = OwnerIndexHead::LEN,
+        seeds = [b"owner_index_head", user.key().as_ref()],
+        bump
+    )]
+    pub owner_index_head: Box<Account<'info, OwnerIndexHead>>,
+    
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = OwnerIndexPage::LEN,
+        seeds = [b"owner_index", user.key().as_ref(), owner_index_head.current_page.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub owner_index: Box<Account<'info, OwnerIndexPage>>,
+    
+    /// CHECK: SlotHashes sysvar，读取最新条目锚定确权
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+    
+    // 可选：以订阅额度抵扣确权费用
+    #[account(
+        mut,
+        seeds = [b"subscription", user.key().as_ref()],
+        bump
+    )]
+    pub subscription: Option<Box<Account<'info, Subscription>>>,
+    
+    // 新版本或衍生作品引用的父确权记录 (原创作品不传)
+    pub parent_claim: Option<Account<'info, OwnershipClaim>>,
+    
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Box<Account<'info, MscTokenConfig>>,
+    
     #[account(mut)]
     pub user_token_account: Account<'info, TokenAccount>,
     
@@ -51,85 +128,1245 @@ pub struct PayAndCreateClaim<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
     
+    // 以下账户仅在同时铸造确权证书 NFT 时传入
+    #[account(
+        init,
+        payer = user,
+        mint::decimals = 0,
+        mint::authority = certificate_authority,
+    )]
+    pub certificate_mint: Option<Box<Account<'info, Mint>>>,
+    
+    #[account(
+        init,
+        payer = user,
+        associated_token::mint = certificate_mint,
+        associated_token::authority = user,
+    )]
+    pub certificate_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    
+    /// CHECK: Metaplex metadata PDA, validated by the token metadata program
+    #[account(mut)]
+    pub certificate_metadata: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: PDA acting as certificate mint and metadata update authority
+    #[account(
+        seeds = [b"certificate_authority"],
+        bump
+    )]
+    pub certificate_authority: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"service_config", [SERVICE_CERTIFICATE].as_ref()],
+        bump
+    )]
+    pub certificate_service_config: Option<Box<Account<'info, ServiceConfig>>>,
+    
+    // 证书服务配置登记的金库，接收证书费用
+    #[account(mut)]
+    pub certificate_treasury_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+    pub rent: Option<Sysvar<'info, Rent>>,
+    
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
+// 中继代付版本：relayer 支付租金和交易费，用户仅作为交易签名者授权
+#[derive(Accounts)]
+#[instruction(max_price: u64, file_hash: String)]
+pub struct RelayedPayAndCreateClaim<'info> {
+    #[account(
+        init,
+        payer = relayer,
+        space = PaymentRecord::LEN,
+        seeds = [b"payment", user.key().as_ref(), file_hash.as_bytes()],
+        bump
+    )]
+    pub payment_record: Box<Account<'info, PaymentRecord>>,
+    
+    #[account(
+        init,
+        payer = relayer,
+        space = OwnershipClaim::LEN,
+        seeds = [b"claim", user.key().as_ref(), file_hash.as_bytes()],
+        bump
+    )]
+    pub claim: Box<Account<'info, OwnershipClaim>>,
+    
+    #[account(
+        seeds = [b"service_config", [SERVICE_CLAIM].as_ref()],
+        bump
+    )]
+    pub service_config: Box<Account<'info, ServiceConfig>>,
+    
+    #[account(
+        mut,
+        seeds = [b"global_state"],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+    
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = UserStats::LEN,
+        seeds = [b"user_stats", user.key().as_ref()],
+        bump
+    )]
+    pub user_stats: Box<Account<'info, UserStats>>,
+    
+    // 所有者索引头与当前索引页 (首次使用时创建，无需事先初始化)
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = OwnerIndexHead::LEN,
+        seeds = [b"owner_index_head", user.key().as_ref()],
+        bump
+    )]
+    pub owner_index_head: Box<Account<'info, OwnerIndexHead>>,
+    
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = OwnerIndexPage::LEN,
+        seeds = [b"owner_index", user.key().as_ref(), owner_index_head.current_page.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub owner_index: Box<Account<'info, OwnerIndexPage>>,
+    
+    /// CHECK: SlotHashes sysvar，读取最新条目锚定确权
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+    
+    // 可选：以订阅额度抵扣确权费用
+    #[account(
+        mut,
+        seeds = [b"subscription", user.key().as_ref()],
+        bump
+    )]
+    pub subscription: Option<Box<Account<'info, Subscription>>>,
+    
+    pub parent_claim: Option<Account<'info, OwnershipClaim>>,
+    
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Box<Account<'info, MscTokenConfig>>,
+    
+    #[account(mut)]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    
+    // relayer 接收 MSC 补偿的代币账户
+    #[account(mut)]
+    pub relayer_token_account: Account<'info, TokenAccount>,
+    
+    // 用户签名授权扣款，但不支付 SOL
+    pub user: Signer<'info>,
+    
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
 
+#[derive(Accounts)]
+#[instruction(max_usdc_amount: u64, file_hash: String)]
+pub struct ExchangePayAndCreateClaim<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = PaymentRecord::LEN,
+        seeds = [b"payment", user.key().as_ref(), file_hash.as_bytes()],
+        bump
+    )]
+    pub payment_record: Box<Account<'info, PaymentRecord>>,
+    
+    #[account(
+        init,
+        payer = user,
+        space = OwnershipClaim::LEN,
+        seeds = [b"claim", user.key().as_ref(), file_hash.as_bytes()],
+        bump
+    )]
+    pub claim: Box<Account<'info, OwnershipClaim>>,
+    
+    #[account(
+        seeds = [b"service_config", [SERVICE_CLAIM].as_ref()],
+        bump
+    )]
+    pub service_config: Box<Account<'info, ServiceConfig>>,
+    
+    #[account(
+        mut,
+        seeds = [b"global_state"],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+    
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserStats::LEN,
+        seeds = [b"user_stats", user.key().as_ref()],
+        bump
+    )]
+    pub user_stats: Box<Account<'info, UserStats>>,
+    
+    // 所有者索引头与当前索引页 (首次使用时创建，无需事先初始化)
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = OwnerIndexHead::LEN,
+        seeds = [b"owner_index_head", user.key().as_ref()],
+        bump
+    )]
+    pub owner_index_head: Box<Account<'info, OwnerIndexHead>>,
+    
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = OwnerIndexPage::LEN,
+        seeds = [b"owner_index", user.key().as_ref(), owner_index_head.current_page.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub owner_index: Box<Account<'info, OwnerIndexPage>>,
+    
+    /// CHECK: SlotHashes sysvar，读取最新条目锚定确权
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+    
+    // 可选：以订阅额度抵扣确权费用
+    #[account(
+        mut,
+        seeds = [b"subscription", user.key().as_ref()],
+        bump
+    )]
+    pub subscription: Option<Box<Account<'info, Subscription>>>,
+    
+    pub parent_claim: Option<Account<'info, OwnershipClaim>>,
+    
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Box<Account<'info, MscTokenConfig>>,
+    
+    #[account(
+        mut,
+        seeds = [b"exchange_pool"],
+        bump
+    )]
+    pub exchange_pool: Box<Account<'info, ExchangePool>>,
+    
+    // 池子 MSC 金库须由兑换池 PDA 控制，由程序签名划出 MSC
+    #[account(
+        mut,
+        address = exchange_pool.msc_vault @ MscError::InvalidRecipient,
+        constraint = pool_msc_vault.owner == exchange_pool.key() @ MscError::InvalidAccountOwner
+    )]
+    pub pool_msc_vault: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        address = exchange_pool.usdc_vault @ MscError::InvalidRecipient
+    )]
+    pub pool_usdc_vault: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        constraint = user_usdc_account.mint == exchange_pool.usdc_mint @ MscError::InvalidMint,
+        constraint = user_usdc_account.owner == user.key() @ MscError::InvalidAccountOwner
+    )]
+    pub user_usdc_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub treasury_token_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
 
-// 获取确权服务价格
-pub fn get_claim_price() -> u64 {
-    CLAIM_PRICE
+#[derive(Accounts)]
+pub struct InitializeGlobalState<'info> {
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Account<'info, MscTokenConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = GlobalState::LEN,
+        seeds = [b"global_state"],
+        bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
 }
 
-// Account 结构定义
+// 初始化全局状态 (仅限管理员)
+pub fn initialize_global_state(
+    ctx: Context<InitializeGlobalState>,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    require!(config.is_initialized, MscError::AccountNotInitialized);
+    require!(ctx.accounts.authority.key() == config.authority, MscError::InvalidAuthority);
+    
+    let global_state = &mut ctx.accounts.global_state;
+    global_state.authority = ctx.accounts.authority.key();
+    global_state.receipt_counter = 0;
+    
+    msg!("Global state initialized");
+    Ok(())
+}
 
+#[derive(Accounts)]
+#[instruction(service_id: u8)]
+pub struct InitializeServiceConfig<'info> {
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Account<'info, MscTokenConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = ServiceConfig::LEN,
+        seeds = [b"service_config", [service_id].as_ref()],
+        bump
+    )]
+    pub service_config: Account<'info, ServiceConfig>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
 
+#[derive(Accounts)]
+pub struct UpdateServiceConfig<'info> {
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Account<'info, MscTokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"service_config", [service_config.service_id].as_ref()],
+        bump
+    )]
+    pub service_config: Account<'info, ServiceConfig>,
+    
+    pub authority: Signer<'info>,
+}
 
-// 辅助函数：验证支付金额
-pub fn is_valid_payment_amount(amount: u64) -> bool {
-    amount >= CLAIM_PRICE
+// 创建服务配置 (仅限管理员)
+pub fn initialize_service_config(
+    ctx: Context<InitializeServiceConfig>,
+    service_id: u8,
+    price: u64,
+    treasury: Pubkey,
+    name: String,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    require!(config.is_initialized, MscError::AccountNotInitialized);
+    require!(ctx.accounts.authority.key() == config.authority, MscError::InvalidAuthority);
+    require!(service_id < SERVICE_COUNT, MscError::InvalidServiceType);
+    require!(name.len() <= ServiceConfig::MAX_NAME_LEN, MscError::ServiceNameTooLong);
+    
+    let clock = Clock::get()?;
+    let service_config = &mut ctx.accounts.service_config;
+    service_config.service_id = service_id;
+    service_config.price = price;
+    service_config.is_enabled = true;
+    service_config.treasury = treasury;
+    service_config.name = name;
+    service_config.updated_at = clock.unix_timestamp;
+    
+    msg!("Service config initialized:");
+    msg!("Service: {} ({})", service_config.name, service_id);
+    msg!("Price: {} MSC", price);
+    msg!("Treasury: {}", treasury);
+    
+    Ok(())
+}
+
+// 更新服务配置 (仅限管理员)
+pub fn update_service_config(
+    ctx: Context<UpdateServiceConfig>,
+    price: u64,
+    is_enabled: bool,
+    treasury: Pubkey,
+    name: String,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    require!(config.is_initialized, MscError::AccountNotInitialized);
+    require!(ctx.accounts.authority.key() == config.authority, MscError::InvalidAuthority);
+    require!(name.len() <= ServiceConfig::MAX_NAME_LEN, MscError::ServiceNameTooLong);
+    
+    let clock = Clock::get()?;
+    let service_config = &mut ctx.accounts.service_config;
+    let old_price = service_config.price;
+    service_config.price = price;
+    service_config.is_enabled = is_enabled;
+    service_config.treasury = treasury;
+    service_config.name = name;
+    service_config.updated_at = clock.unix_timestamp;
+    
+    msg!("Service config updated:");
+    msg!("Service: {} ({})", service_config.name, service_config.service_id);
+    msg!("Old Price: {}", old_price);
+    msg!("New Price: {}", price);
+    msg!("Enabled: {}", is_enabled);
+    
+    Ok(())
+}
+
+// 辅助函数：读取已启用服务的价格
+pub fn get_service_price(service_config: &ServiceConfig, service_id: u8) -> Result<u64> {
+    require!(service_config.service_id == service_id, MscError::InvalidServiceType);
+    require!(service_config.is_enabled, MscError::ServiceDisabled);
+    Ok(service_config.price)
+}
+
+// 辅助函数：校验付款账户与金库账户
+// 金库必须是服务配置中登记的账户，双方代币账户均须为 MSC mint，付款账户须属于付款人
+pub fn validate_payment_accounts(
+    config: &MscTokenConfig,
+    service_config: &ServiceConfig,
+    user_token_account: &TokenAccount,
+    treasury_token_account: &Account<TokenAccount>,
+    payer: Pubkey,
+) -> Result<()> {
+    require!(
+        treasury_token_account.key() == service_config.treasury,
+        MscError::InvalidTreasury
+    );
+    require!(treasury_token_account.mint == config.mint, MscError::InvalidMint);
+    require!(user_token_account.mint == config.mint, MscError::InvalidMint);
+    require!(user_token_account.owner == payer, MscError::InvalidAccountOwner);
+    Ok(())
 }
 
-// 辅助函数：获取服务名称
-pub fn get_service_name() -> &'static str {
-    "确权服务"
+// 获取批量确权价格 (按确权服务单价计价并应用阶梯折扣)
+pub fn get_batch_claim_price(unit_price: u64, count: u32) -> Result<u64> {
+    require!(count > 0 && count <= MAX_BATCH_CLAIM_COUNT, MscError::InvalidBatchSize);
+    
+    let discount_bps = BATCH_DISCOUNT_TIERS
+        .iter()
+        .find(|(min_count, _)| count >= *min_count)
+        .map(|(_, discount)| *discount)
+        .unwrap_or(0);
+    
+    let full_price = unit_price
+        .checked_mul(count as u64)
+        .ok_or(MscError::MathOverflow)?;
+    let price = full_price
+        .checked_mul(10_000 - discount_bps)
+        .ok_or(MscError::MathOverflow)?
+        .checked_div(10_000)
+        .ok_or(MscError::DivisionByZero)?;
+    
+    Ok(price)
+}
+
+// 辅助函数：从用户代币账户收取服务费用到金库
+pub fn charge_service_fee<'info>(
+    token_program: &Program<'info, Token>,
+    from: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    authority: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    require!(from.amount >= amount, MscError::InsufficientBalance);
+    
+    let cpi_accounts = Transfer {
+        from: from.to_account_info(),
+        to: to.to_account_info(),
+        authority,
+    };
+    let cpi_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts);
+    token::transfer(cpi_ctx, amount)
+}
+
+// 辅助函数：写入已完成的支付记录
+pub fn record_completed_payment(
+    payment_record: &mut PaymentRecord,
+    payer: Pubkey,
+    service_id: u8,
+    amount: u64,
+    timestamp: i64,
+    sequence: u64,
+) {
+    payment_record.payer = payer;
+    payment_record.amount = amount;
+    payment_record.timestamp = timestamp;
+    payment_record.sequence = sequence;
+    payment_record.transaction_id = generate_transaction_id(sequence);
+    payment_record.status = PaymentRecord::STATUS_COMPLETED;
+    payment_record.is_used = true; // 标记为已使用
+    payment_record.service_id = service_id;
+    payment_record.refundable_after = 0;
+}
+
+// 确权统计 (用户统计与组织统计)
+pub trait ClaimStats {
+    fn record_claim(&mut self, payer: Pubkey, amount: u64, timestamp: i64) -> Result<()>;
+}
+
+impl ClaimStats for UserStats {
+    fn record_claim(&mut self, payer: Pubkey, amount: u64, timestamp: i64) -> Result<()> {
+        self.user = payer;
+        self.total_payments = self.total_payments.checked_add(amount).ok_or(MscError::MathOverflow)?;
+        self.total_claims = self.total_claims.checked_add(1).ok_or(MscError::MathOverflow)?;
+        self.last_activity = timestamp;
+        Ok(())
+    }
+}
+
+impl ClaimStats for OrgStats {
+    fn record_claim(&mut self, _payer: Pubkey, amount: u64, timestamp: i64) -> Result<()> {
+        self.total_payments = self.total_payments.checked_add(amount).ok_or(MscError::MathOverflow)?;
+        self.total_claims = self.total_claims.checked_add(1).ok_or(MscError::MathOverflow)?;
+        self.last_activity = timestamp;
+        Ok(())
+    }
+}
+
+// 新确权登记涉及的账户
+pub struct ClaimRegistration<'a, 'info> {
+    pub claim: &'a mut Account<'info, OwnershipClaim>,
+    pub payment_record: &'a mut PaymentRecord,
+    pub global_state: &'a mut GlobalState,
+    pub owner_index_head: &'a mut OwnerIndexHead,
+    pub owner_index: &'a mut OwnerIndexPage,
+    pub stats: &'a mut dyn ClaimStats,
+}
+
+// 新确权登记参数
+pub struct NewClaim<'a> {
+    pub owner: Pubkey,
+    pub payer: Pubkey,
+    pub file_hash: &'a str,
+    pub lineage: &'a ClaimLineage,
+    pub amount: u64,
+    // 同时铸造证书时的证书服务费用 (单独记入支付记录)
+    pub certificate_amount: u64,
+    pub timestamp: i64,
+    // 先提交后揭示的确权沿用提交交易中读取的账本锚点
+    pub ledger: LedgerAnchor,
+    // 先提交后揭示的确权沿用提交时分配的支付回执序号
+    pub payment_sequence: Option<u64>,
+}
+
+// 辅助函数：所有确权创建路径在收费后共用的登记流程
+// 写入支付记录 → 写入确权 → 锚定账本并关联支付回执 → 登记所有者索引 → 更新统计，返回确权回执序号
+pub fn register_new_claim(accounts: ClaimRegistration, new_claim: NewClaim) -> Result<u64> {
+    let payment_sequence = match new_claim.payment_sequence {
+        Some(sequence) => sequence,
+        None => accounts.global_state.next_receipt()?,
+    };
+    let claim_sequence = accounts.global_state.next_receipt()?;
+    
+    record_completed_payment(
+        accounts.payment_record,
+        new_claim.payer,
+        SERVICE_CLAIM,
+        new_claim.amount,
+        new_claim.timestamp,
+        payment_sequence,
+    );
+    accounts.payment_record.certificate_amount = new_claim.certificate_amount;
+    
+    write_new_claim(
+        accounts.claim,
+        new_claim.owner,
+        new_claim.file_hash.to_string(),
+        new_claim.timestamp,
+        new_claim.lineage,
+        claim_sequence,
+    );
+    
+    anchor_claim_to_ledger(accounts.claim, &new_claim.ledger);
+    accounts.claim.payment_sequence = payment_sequence;
+    
+    index_claim(accounts.owner_index_head, accounts.owner_index, accounts.claim)?;
+    
+    accounts.stats.record_claim(
+        new_claim.payer,
+        new_claim
+            .amount
+            .checked_add(new_claim.certificate_amount)
+            .ok_or(MscError::MathOverflow)?,
+        Clock::get()?.unix_timestamp,
+    )?;
+    
+    Ok(claim_sequence)
 }
 
 // 支付并创建确权记录（原子操作）
 pub fn pay_and_create_claim(
     ctx: Context<PayAndCreateClaim>,
-    amount: u64,
+    max_price: u64,
     file_hash: String,
+    relation: ClaimRelation,
 ) -> Result<()> {
     // 验证文件哈希
     require!(validate_file_hash(&file_hash), MscError::InvalidFileHash);
     
-    // 验证支付金额
-    require!(amount >= CLAIM_PRICE, MscError::PaymentAmountTooLow);
-    require!(ctx.accounts.user_token_account.amount >= amount, MscError::InsufficientBalance);
+    // 验证版本谱系
+    let lineage = resolve_claim_lineage(
+        ctx.accounts.parent_claim.as_ref(),
+        relation,
+        &ctx.accounts.user.key(),
+    )?;
     
+    // 验证支付账户 (铸造证书时另向证书服务金库支付证书费用)
+    validate_payment_accounts(
+        &ctx.accounts.config,
+        &ctx.accounts.service_config,
+        &ctx.accounts.user_token_account,
+        &ctx.accounts.treasury_token_account,
+        ctx.accounts.user.key(),
+    )?;
     let clock = Clock::get()?;
+    let claim_price = get_service_price(&ctx.accounts.service_config, SERVICE_CLAIM)?;
     
-    // 执行代币转账
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.user_token_account.to_account_info(),
-        to: ctx.accounts.treasury_token_account.to_account_info(),
-        authority: ctx.accounts.user.to_account_info(),
+    // 传入订阅账户时扣减订阅额度，不再收取确权费用
+    let price = apply_claim_credits(
+        claim_price,
+        ctx.accounts.subscription.as_deref_mut().map(|s| &mut **s),
+        1,
+        clock.unix_timestamp,
+    )?;
+    let certificate_price = match ctx.accounts.certificate_mint.as_ref() {
+        Some(_) => {
+            let certificate_service_config = ctx
+                .accounts
+                .certificate_service_config
+                .as_ref()
+                .ok_or(MscError::CertificateAccountsMissing)?;
+            let certificate_treasury = ctx
+                .accounts
+                .certificate_treasury_token_account
+                .as_ref()
+                .ok_or(MscError::CertificateAccountsMissing)?;
+            validate_payment_accounts(
+                &ctx.accounts.config,
+                certificate_service_config,
+                &ctx.accounts.user_token_account,
+                certificate_treasury,
+                ctx.accounts.user.key(),
+            )?;
+            get_service_price(certificate_service_config, SERVICE_CERTIFICATE)?
+        }
+        None => 0,
     };
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-    token::transfer(cpi_ctx, amount)?;
+    let total_price = price.checked_add(certificate_price).ok_or(MscError::MathOverflow)?;
+    require!(total_price <= max_price, MscError::PriceAboveMaximum);
     
-    // 记录支付信息
-    let payment_record = &mut ctx.accounts.payment_record;
-    payment_record.payer = ctx.accounts.user.key();
-    payment_record.amount = amount;
-    payment_record.timestamp = clock.unix_timestamp;
-    payment_record.transaction_id = ctx.accounts.user.key().to_string();
-    payment_record.status = 1; // 已完成
-    payment_record.is_used = true; // 标记为已使用
+    let user = ctx.accounts.user.key();
+    
+    // 执行代币转账：确权费用划入确权服务金库，证书费用划入证书服务金库
+    if price > 0 {
+        charge_service_fee(
+            &ctx.accounts.token_program,
+            &ctx.accounts.user_token_account,
+            &ctx.accounts.treasury_token_account,
+            ctx.accounts.user.to_account_info(),
+            price,
+        )?;
+    }
+    if let Some(certificate_treasury) = ctx.accounts.certificate_treasury_token_account.as_ref() {
+        if certificate_price > 0 {
+            charge_service_fee(
+                &ctx.accounts.token_program,
+                &ctx.accounts.user_token_account,
+                certificate_treasury,
+                ctx.accounts.user.to_account_info(),
+                certificate_price,
+            )?;
+        }
+    }
     
-    // 创建确权记录
-    let claim = &mut ctx.accounts.claim;
-    claim.owner = ctx.accounts.user.key();
-    claim.file_hash = file_hash.clone();
-    claim.timestamp = clock.unix_timestamp;
-    claim.transaction_id = ctx.accounts.user.key().to_string();
-    claim.is_active = true;
-    
-    // 更新用户统计
-    let user_stats = &mut ctx.accounts.user_stats;
-    user_stats.user = ctx.accounts.user.key();
-    user_stats.total_payments = user_stats.total_payments.checked_add(amount).ok_or(MscError::MathOverflow)?;
-    user_stats.total_claims = user_stats.total_claims.checked_add(1).ok_or(MscError::MathOverflow)?;
-    user_stats.last_activity = clock.unix_timestamp;
+    let claim_sequence = register_new_claim(
+        ClaimRegistration {
+            claim: &mut ctx.accounts.claim,
+            payment_record: &mut ctx.accounts.payment_record,
+            global_state: &mut ctx.accounts.global_state,
+            owner_index_head: &mut ctx.accounts.owner_index_head,
+            owner_index: &mut ctx.accounts.owner_index,
+            stats: &mut *ctx.accounts.user_stats,
+        },
+        NewClaim {
+            owner: user,
+            payer: user,
+            file_hash: &file_hash,
+            lineage: &lineage,
+            amount: price,
+            certificate_amount: certificate_price,
+            timestamp: clock.unix_timestamp,
+            ledger: read_ledger_anchor(clock.slot, &ctx.accounts.slot_hashes)?,
+            payment_sequence: None,
+        },
+    )?;
+    
+    // 可选：铸造确权证书 NFT
+    let certificate_mint = mint_claim_certificate(ctx.accounts, ctx.bumps.certificate_authority)?;
+    ctx.accounts.claim.certificate_mint = certificate_mint;
     
     msg!("Payment and claim creation completed:");
-    msg!("User: {}", ctx.accounts.user.key());
-    msg!("Amount: {} MSC", amount);
+    msg!("User: {}", user);
+    msg!("Amount: {} MSC", price);
+    msg!("Certificate Fee: {} MSC", certificate_price);
     msg!("File Hash: {}", file_hash);
+    msg!("Receipt: {}", generate_transaction_id(claim_sequence));
+    msg!("Version: {}", lineage.version);
+    if let Some(parent) = lineage.parent {
+        msg!("Parent Claim: {}", parent);
+    }
     msg!("Timestamp: {}", clock.unix_timestamp);
     
     Ok(())
-}
\ No newline at end of file
+}
+
+// 中继代付并创建确权记录（原子操作）
+// 用户对整笔交易签名即授权 max_price 与 relayer_fee，交易签名本身防止重放
+pub fn relayed_pay_and_create_claim(
+    ctx: Context<RelayedPayAndCreateClaim>,
+    max_price: u64,
+    file_hash: String,
+    relation: ClaimRelation,
+    relayer_fee: u64,
+) -> Result<()> {
+    require!(validate_file_hash(&file_hash), MscError::InvalidFileHash);
+    
+    let lineage = resolve_claim_lineage(
+        ctx.accounts.parent_claim.as_ref(),
+        relation,
+        &ctx.accounts.user.key(),
+    )?;
+    
+    validate_payment_accounts(
+        &ctx.accounts.config,
+        &ctx.accounts.service_config,
+        &ctx.accounts.user_token_account,
+        &ctx.accounts.treasury_token_account,
+        ctx.accounts.user.key(),
+    )?;
+    let clock = Clock::get()?;
+    
+    // 传入订阅账户时扣减订阅额度，不再收取确权费用 (relayer 补偿照常支付)
+    let price = apply_claim_credits(
+        get_service_price(&ctx.accounts.service_config, SERVICE_CLAIM)?,
+        ctx.accounts.subscription.as_deref_mut().map(|s| &mut **s),
+        1,
+        clock.unix_timestamp,
+    )?;
+    require!(price <= max_price, MscError::PriceAboveMaximum);
+    
+    // relayer 补偿必须以 MSC 支付到 relayer 自己的账户
+    let relayer_token_account = &ctx.accounts.relayer_token_account;
+    require!(
+        relayer_token_account.owner == ctx.accounts.relayer.key(),
+        MscError::InvalidRecipient
+    );
+    require!(
+        relayer_token_account.mint == ctx.accounts.user_token_account.mint,
+        MscError::InvalidMint
+    );
+    
+    let total_cost = price.checked_add(relayer_fee).ok_or(MscError::MathOverflow)?;
+    require!(ctx.accounts.user_token_account.amount >= total_cost, MscError::InsufficientBalance);
+    
+    let user = ctx.accounts.user.key();
+    
+    if price > 0 {
+        charge_service_fee(
+            &ctx.accounts.token_program,
+            &ctx.accounts.user_token_account,
+            &ctx.accounts.treasury_token_account,
+            ctx.accounts.user.to_account_info(),
+            price,
+        )?;
+    }
+    
+    if relayer_fee > 0 {
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.user_token_account.to_account_info(),
+            to: ctx.accounts.relayer_token_account.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token::transfer(cpi_ctx, relayer_fee)?;
+    }
+    
+    let claim_sequence = register_new_claim(
+        ClaimRegistration {
+            claim: &mut ctx.accounts.claim,
+            payment_record: &mut ctx.accounts.payment_record,
+            global_state: &mut ctx.accounts.global_state,
+            owner_index_head: &mut ctx.accounts.owner_index_head,
+            owner_index: &mut ctx.accounts.owner_index,
+            stats: &mut **ctx.accounts.user_stats,
+        },
+        NewClaim {
+            owner: user,
+            payer: user,
+            file_hash: &file_hash,
+            lineage: &lineage,
+            amount: price,
+            certificate_amount: 0,
+            timestamp: clock.unix_timestamp,
+            ledger: read_ledger_anchor(clock.slot, &ctx.accounts.slot_hashes)?,
+            payment_sequence: None,
+        },
+    )?;
+    
+    msg!("Relayed payment and claim creation completed:");
+    msg!("User: {}", user);
+    msg!("Relayer: {}", ctx.accounts.relayer.key());
+    msg!("Amount: {} MSC", price);
+    msg!("Relayer Fee: {} MSC", relayer_fee);
+    msg!("File Hash: {}", file_hash);
+    msg!("Receipt: {}", generate_transaction_id(claim_sequence));
+    
+    Ok(())
+}
+
+// 以 USDC 支付并创建确权记录（原子操作）
+// 按兑换池当前汇率报价：用户 USDC 划入池子 USDC 金库，池子 MSC 金库向确权服务金库划出服务价格对应的 MSC
+// max_usdc_amount 同时约束服务价格与汇率变动 (滑点保护)
+pub fn exchange_pay_and_create_claim(
+    ctx: Context<ExchangePayAndCreateClaim>,
+    max_usdc_amount: u64,
+    file_hash: String,
+    relation: ClaimRelation,
+) -> Result<()> {
+    require!(validate_file_hash(&file_hash), MscError::InvalidFileHash);
+    
+    let lineage = resolve_claim_lineage(
+        ctx.accounts.parent_claim.as_ref(),
+        relation,
+        &ctx.accounts.user.key(),
+    )?;
+    
+    let treasury_token_account = &ctx.accounts.treasury_token_account;
+    require!(
+        treasury_token_account.key() == ctx.accounts.service_config.treasury,
+        MscError::InvalidTreasury
+    );
+    require!(treasury_token_account.mint == ctx.accounts.config.mint, MscError::InvalidMint);
+    let clock = Clock::get()?;
+    
+    // 传入订阅账户时扣减订阅额度，无需兑换与支付
+    let price = apply_claim_credits(
+        get_service_price(&ctx.accounts.service_config, SERVICE_CLAIM)?,
+        ctx.accounts.subscription.as_deref_mut().map(|s| &mut **s),
+        1,
+        clock.unix_timestamp,
+    )?;
+    
+    // 按池子当前汇率计算所需 USDC
+    let exchange_pool = &ctx.accounts.exchange_pool;
+    require!(exchange_pool.is_active, MscError::ExchangePoolNotActive);
+    let exchange_rate = exchange_pool.exchange_rate;
+    let (usdc_amount, fee_amount) = calculate_swap_input(price, exchange_rate, exchange_pool.fee_rate)?;
+    require!(usdc_amount <= max_usdc_amount, MscError::PriceAboveMaximum);
+    require!(ctx.accounts.pool_msc_vault.amount >= price, MscError::InsufficientLiquidity);
+    
+    let user = ctx.accounts.user.key();
+    
+    // 用户 USDC 划入池子
+    if usdc_amount > 0 {
+        charge_service_fee(
+            &ctx.accounts.token_program,
+            &ctx.accounts.user_usdc_account,
+            &ctx.accounts.pool_usdc_vault,
+            ctx.accounts.user.to_account_info(),
+            usdc_amount,
+        )?;
+    }
+    
+    // 池子 MSC 划入确权服务金库
+    if price > 0 {
+        let pool_seeds: &[&[u8]] = &[b"exchange_pool", &[ctx.bumps.exchange_pool]];
+        let signer_seeds = &[pool_seeds];
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.pool_msc_vault.to_account_info(),
+            to: ctx.accounts.treasury_token_account.to_account_info(),
+            authority: ctx.accounts.exchange_pool.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        token::transfer(cpi_ctx, price)?;
+    }
+    
+    let exchange_pool = &mut ctx.accounts.exchange_pool;
+    exchange_pool.total_volume = exchange_pool.total_volume
+        .checked_add(price)
+        .ok_or(MscError::MathOverflow)?;
+    
+    let claim_sequence = register_new_claim(
+        ClaimRegistration {
+            claim: &mut ctx.accounts.claim,
+            payment_record: &mut ctx.accounts.payment_record,
+            global_state: &mut ctx.accounts.global_state,
+            owner_index_head: &mut ctx.accounts.owner_index_head,
+            owner_index: &mut ctx.accounts.owner_index,
+            stats: &mut **ctx.accounts.user_stats,
+        },
+        NewClaim {
+            owner: user,
+            payer: user,
+            file_hash: &file_hash,
+            lineage: &lineage,
+            amount: price,
+            certificate_amount: 0,
+            timestamp: clock.unix_timestamp,
+            ledger: read_ledger_anchor(clock.slot, &ctx.accounts.slot_hashes)?,
+            payment_sequence: None,
+        },
+    )?;
+    
+    msg!("Exchange payment and claim creation completed:");
+    msg!("User: {}", user);
+    msg!("Amount: {} MSC", price);
+    msg!("USDC Amount: {}", usdc_amount);
+    msg!("Fee Amount: {}", fee_amount);
+    msg!("Exchange Rate: {}", exchange_rate);
+    msg!("File Hash: {}", file_hash);
+    msg!("Receipt: {}", generate_transaction_id(claim_sequence));
+    
+    Ok(())
+}
+
+// 两阶段支付：资金先托管在程序 escrow 中，服务完成后划入金库，或退回付款人
+#[derive(Accounts)]
+#[instruction(service_id: u8, payment_id: u64)]
+pub struct PayForService<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = PaymentRecord::LEN,
+        seeds = [b"service_payment", payer.key().as_ref(), payment_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub payment_record: Box<Account<'info, PaymentRecord>>,
+    
+    #[account(
+        seeds = [b"service_config", [service_id].as_ref()],
+        bump
+    )]
+    pub service_config: Box<Account<'info, ServiceConfig>>,
+    
+    #[account(
+        mut,
+        seeds = [b"global_state"],
+        bump
+    )]
+    pub global_state: Box<Account<'info, GlobalState>>,
+    
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"payment_escrow", payment_record.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = escrow_authority
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: escrow 代币账户的 PDA 权限账户，不存储数据
+    #[account(
+        seeds = [b"escrow_authority"],
+        bump
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Box<Account<'info, MscTokenConfig>>,
+    
+    #[account(address = config.mint @ MscError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        constraint = user_token_account.mint == mint.key() @ MscError::InvalidMint,
+        constraint = user_token_account.owner == payer.key() @ MscError::InvalidAccountOwner
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FulfillPayment<'info> {
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Account<'info, MscTokenConfig>,
+    
+    #[account(mut)]
+    pub payment_record: Account<'info, PaymentRecord>,
+    
+    #[account(
+        seeds = [b"service_config", [payment_record.service_id].as_ref()],
+        bump
+    )]
+    pub service_config: Account<'info, ServiceConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"payment_escrow", payment_record.key().as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: escrow 代币账户的 PDA 权限账户，不存储数据
+    #[account(
+        seeds = [b"escrow_authority"],
+        bump
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: 仅接收 escrow 账户关闭后返还的租金
+    #[account(mut, address = payment_record.payer)]
+    pub payer: UncheckedAccount<'info>,
+    
+    pub authority: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RefundPayment<'info> {
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Account<'info, MscTokenConfig>,
+    
+    #[account(mut)]
+    pub payment_record: Account<'info, PaymentRecord>,
+    
+    #[account(
+        mut,
+        seeds = [b"payment_escrow", payment_record.key().as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: escrow 代币账户的 PDA 权限账户，不存储数据
+    #[account(
+        seeds = [b"escrow_authority"],
+        bump
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = payer_token_account.mint == escrow_token_account.mint @ MscError::InvalidMint,
+        constraint = payer_token_account.owner == payment_record.payer @ MscError::InvalidRecipient
+    )]
+    pub payer_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: 仅接收 escrow 账户关闭后返还的租金
+    #[account(mut, address = payment_record.payer)]
+    pub payer: UncheckedAccount<'info>,
+    
+    pub authority: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+// 支付服务费用到 escrow，生成待处理支付记录
+pub fn pay_for_service(
+    ctx: Context<PayForService>,
+    service_id: u8,
+    _payment_id: u64,
+    max_price: u64,
+) -> Result<()> {
+    let price = get_service_price(&ctx.accounts.service_config, service_id)?;
+    require!(price <= max_price, MscError::PriceAboveMaximum);
+    
+    charge_service_fee(
+        &ctx.accounts.token_program,
+        &ctx.accounts.user_token_account,
+        &ctx.accounts.escrow_token_account,
+        ctx.accounts.payer.to_account_info(),
+        price,
+    )?;
+    
+    let clock = Clock::get()?;
+    let sequence = ctx.accounts.global_state.next_receipt()?;
+    let refundable_after = clock
+        .unix_timestamp
+        .checked_add(PAYMENT_REFUND_TIMEOUT)
+        .ok_or(MscError::MathOverflow)?;
+    
+    let payment_record = &mut ctx.accounts.payment_record;
+    payment_record.payer = ctx.accounts.payer.key();
+    payment_record.amount = price;
+    payment_record.timestamp = clock.unix_timestamp;
+    payment_record.sequence = sequence;
+    payment_record.transaction_id = generate_transaction_id(sequence);
+    payment_record.status = PaymentRecord::STATUS_PENDING;
+    payment_record.is_used = false;
+    payment_record.service_id = service_id;
+    payment_record.refundable_after = refundable_after;
+    
+    msg!("Service payment pending:");
+    msg!("Payer: {}", payment_record.payer);
+    msg!("Service: {} ({})", ctx.accounts.service_config.name, service_id);
+    msg!("Amount: {} MSC", price);
+    msg!("Receipt: {}", payment_record.transaction_id);
+    msg!("Refundable After: {}", refundable_after);
+    
+    Ok(())
+}
+
+// 服务完成：escrow 资金划入金库 (仅限管理员)
+pub fn fulfill_payment(
+    ctx: Context<FulfillPayment>,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    require!(ctx.accounts.authority.key() == config.authority, MscError::InvalidAuthority);
+    require!(
+        ctx.accounts.payment_record.status == PaymentRecord::STATUS_PENDING,
+        MscError::PaymentNotPending
+    );
+    
+    let treasury_token_account = &ctx.accounts.treasury_token_account;
+    require!(
+        treasury_token_account.key() == ctx.accounts.service_config.treasury,
+        MscError::InvalidTreasury
+    );
+    require!(treasury_token_account.mint == config.mint, MscError::InvalidMint);
+    
+    let amount = ctx.accounts.payment_record.amount;
+    release_payment_escrow(
+        &ctx.accounts.token_program,
+        &ctx.accounts.escrow_token_account,
+        &ctx.accounts.treasury_token_account,
+        &ctx.accounts.escrow_authority,
+        ctx.accounts.payer.to_account_info(),
+        ctx.bumps.escrow_authority,
+        amount,
+    )?;
+    
+    let payment_record = &mut ctx.accounts.payment_record;
+    payment_record.status = PaymentRecord::STATUS_COMPLETED;
+    payment_record.is_used = true;
+    
+    msg!("Service payment fulfilled:");
+    msg!("Receipt: {}", payment_record.transaction_id);
+    msg!("Amount: {} MSC", amount);
+    
+    Ok(())
+}
+
+// 退款：管理员可随时退款，付款人需等待超时
+pub fn refund_payment(
+    ctx: Context<RefundPayment>,
+) -> Result<()> {
+    let signer = ctx.accounts.authority.key();
+    let is_admin = signer == ctx.accounts.config.authority;
+    let payment_record = &ctx.accounts.payment_record;
+    require!(is_admin || signer == payment_record.payer, MscError::InvalidAuthority);
+    require!(payment_record.status == PaymentRecord::STATUS_PENDING, MscError::PaymentNotPending);
+    
+    if !is_admin {
+        let clock = Clock::get()?;
+        require!(
+            clock.unix_timestamp >= payment_record.refundable_after,
+            MscError::RefundNotAvailable
+        );
+    }
+    
+    let amount = payment_record.amount;
+    release_payment_escrow(
+        &ctx.accounts.token_program,
+        &ctx.accounts.escrow_token_account,
+        &ctx.accounts.payer_token_account,
+        &ctx.accounts.escrow_authority,
+        ctx.accounts.payer.to_account_info(),
+        ctx.bumps.escrow_authority,
+        amount,
+    )?;
+    
+    let payment_record = &mut ctx.accounts.payment_record;
+    payment_record.status = PaymentRecord::STATUS_REFUNDED;
+    
+    msg!("Service payment refunded:");
+    msg!("Receipt: {}", payment_record.transaction_id);
+    msg!("Amount: {} MSC", amount);
+    msg!("By Admin: {}", is_admin);
+    
+    Ok(())
+}
+
+// 辅助函数：将 escrow 资金转出并关闭 escrow 账户，租金返还付款人 (争议保证金复用)
+pub fn release_payment_escrow<'info>(
+    token_program: &Program<'info, Token>,
+    escrow: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    escrow_authority: &UncheckedAccount<'info>,
+    rent_receiver: AccountInfo<'info>,
+    bump: u8,
+    amount: u64,
+) -> Result<()> {
+    let seeds: &[&[u8]] = &[b"escrow_authority", &[bump]];
+    let signer = &[seeds];
+    
+    let cpi_accounts = Transfer {
+        from: escrow.to_account_info(),
+        to: to.to_account_info(),
+        authority: escrow_authority.to_account_info(),
+    };
+    token::transfer(
+        CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer),
+        amount,
+    )?;
+    
+    let cpi_accounts = CloseAccount {
+        account: escrow.to_account_info(),
+        destination: rent_receiver,
+        authority: escrow_authority.to_account_info(),
+    };
+    token::close_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        cpi_accounts,
+        signer,
+    ))
+}
@@ -1,5 +1,25 @@
 use anchor_lang::prelude::*;
 
+// 全局状态 (程序级回执计数器)
+#[account]
+pub struct GlobalState {
+    pub authority: Pubkey,
+    pub receipt_counter: u64, // 已分配的最大回执序号
+}
+
+impl GlobalState {
+    pub const LEN: usize = 8 + 32 + 8;
+
+    // 分配下一个回执序号 (从 1 开始单调递增)
+    pub fn next_receipt(&mut self) -> Result<u64> {
+        self.receipt_counter = self
+            .receipt_counter
+            .checked_add(1)
+            .ok_or(crate::error::MscError::MathOverflow)?;
+        Ok(self.receipt_counter)
+    }
+}
+
 // MSC Token 配置
 #[account]
 pub struct MscTokenConfig {
@@ -20,12 +40,558 @@ pub struct OwnershipClaim {
     pub owner: Pubkey,
     pub file_hash: String,
     pub timestamp: i64,
+    pub sequence: u64, // 全局回执序号
     pub transaction_id: String,
     pub is_active: bool,
+    pub is_co_owned: bool, // 是否由共有人账户管理
+    pub parent: Option<Pubkey>, // 上一版本或原作的确权记录
+    pub version: u32, // 同一作品的版本号，原作为 1
+    pub relation: ClaimRelation,
+    pub notary: Option<Pubkey>, // 共同证明该确权的公证人
+    pub hash_algorithm: HashAlgorithm,
+    pub certificate_mint: Option<Pubkey>, // 确权证书 NFT，持有人即所有者
+    pub expires_at: i64, // 有效期截止时间，过期后宽限期内仍可续期
+    pub is_disputed: bool, // 存在未裁决的争议
+    pub is_listed: bool, // 挂牌出售中，锁定转让
+    pub index_page: Option<u32>, // 所在所有者索引页，未建索引时为 None
+    pub slot: u64, // 确权时间戳确定时的 slot
+    pub recent_slot: u64, // 写入时 SlotHashes 最新条目的 slot
+    pub recent_slot_hash: [u8; 32], // 对应的 slot 哈希，证明确权不早于该账本位置
+    pub proposal_count: u64, // 共有人提案累计数，共有人账户关闭重建后继续递增
+    pub payment_sequence: u64, // 对应支付记录的回执序号，关联付款交易
 }
 
 impl OwnershipClaim {
-    pub const LEN: usize = 8 + 32 + 4 + 64 + 8 + 4 + 64 + 1; // 预留64字节给字符串
+    pub const LEN: usize = 8 + 32 + 4 + 64 + 8 + 8 + 4 + 64 + 1 + 1 + (1 + 32) + 4 + 1 + (1 + 32) + 1 + (1 + 32) + 8 + 1 + 1 + (1 + 4) + 8 + 8 + 32 + 8 + 8; // 预留64字节给字符串
+    
+    pub const TERM: i64 = 365 * 24 * 60 * 60; // 有效期 1 年
+    pub const GRACE_PERIOD: i64 = 30 * 24 * 60 * 60; // 宽限期 30 天
+
+    pub fn status(&self, now: i64) -> ClaimStatus {
+        if !self.is_active {
+            ClaimStatus::Revoked
+        } else if self.is_disputed {
+            ClaimStatus::Disputed
+        } else if self.is_expired(now) {
+            ClaimStatus::Expired
+        } else {
+            ClaimStatus::Active
+        }
+    }
+    
+    pub fn is_expired(&self, now: i64) -> bool {
+        now > self.expires_at
+    }
+    
+    // 宽限期结束后不可续期，记录可被关闭以便重新确权
+    pub fn is_past_grace_period(&self, now: i64) -> bool {
+        now > self.expires_at.saturating_add(Self::GRACE_PERIOD)
+    }
+}
+
+// 文件哈希算法
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha256,
+}
+
+// 确权状态
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimStatus {
+    Active,
+    Revoked,
+    Expired,
+    Disputed,
+}
+
+// verify_claim 通过 return data 返回的验证结果
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ClaimVerification {
+    pub claim: Pubkey,
+    pub owner: Pubkey,
+    pub timestamp: i64,
+    pub status: ClaimStatus,
+    pub algorithm: HashAlgorithm,
+    pub expires_at: i64,
+    pub slot: u64,
+    pub recent_slot: u64,
+    pub recent_slot_hash: [u8; 32],
+    pub is_co_owned: bool, // 为 true 时所有权以共有人账户为准，owner 仅为设置共有前的所有者
+}
+
+// 确权记录与父记录的关系
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimRelation {
+    Original,   // 原创作品
+    Revision,   // 同一作品的新版本 (仅限父记录所有者)
+    Derivative, // 基于父记录的衍生作品
+}
+
+// 确权元数据 (伴随账户，按字段实际长度分配空间)
+#[account]
+pub struct ClaimMetadata {
+    pub claim: Pubkey,
+    pub version: u32, // 每次更新递增
+    pub title: String,
+    pub content_type: String, // MIME 类型，如 image/png
+    pub uri: String, // 链下存储地址 (Arweave/IPFS)
+    pub license: String, // SPDX 或 CC 许可证标识
+    pub updated_at: i64,
+}
+
+impl ClaimMetadata {
+    pub const MAX_TITLE_LEN: usize = 128;
+    pub const MAX_CONTENT_TYPE_LEN: usize = 64;
+    pub const MAX_URI_LEN: usize = 200;
+    pub const MAX_LICENSE_LEN: usize = 32;
+
+    pub fn space(title: &str, content_type: &str, uri: &str, license: &str) -> usize {
+        8 + 32 + 4
+            + 4 + title.len()
+            + 4 + content_type.len()
+            + 4 + uri.len()
+            + 4 + license.len()
+            + 8
+    }
+}
+
+// 共有人份额
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct CoOwnerShare {
+    pub owner: Pubkey,
+    pub share_bps: u16, // 份额 (基点，合计 10000)
+}
+
+// 确权共有人 (伴随账户)
+#[account]
+pub struct ClaimCoOwners {
+    pub claim: Pubkey,
+    pub approval_threshold: u8, // 执行提案所需的共有人批准数
+    pub first_proposal: u64, // 设立时确权的提案计数，编号更小的提案属于此前的共有人组合
+    pub co_owners: Vec<CoOwnerShare>,
+}
+
+impl ClaimCoOwners {
+    pub const MAX_CO_OWNERS: usize = 10;
+    pub const TOTAL_SHARE_BPS: u16 = 10_000;
+    pub const LEN: usize = 8 + 32 + 1 + 8 + 4 + Self::MAX_CO_OWNERS * (32 + 2);
+
+    pub fn share_of(&self, owner: &Pubkey) -> Option<u16> {
+        self.co_owners
+            .iter()
+            .find(|c| c.owner == *owner)
+            .map(|c| c.share_bps)
+    }
+
+    pub fn is_co_owner(&self, owner: &Pubkey) -> bool {
+        self.share_of(owner).is_some()
+    }
+}
+
+// 共有人提案动作
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum ClaimAction {
+    Transfer { new_owner: Pubkey },
+    Revoke,
+}
+
+impl ClaimAction {
+    // 转让提案的受让人 (撤销提案返回默认地址)
+    pub fn recipient(&self) -> Pubkey {
+        match self {
+            ClaimAction::Transfer { new_owner } => *new_owner,
+            ClaimAction::Revoke => Pubkey::default(),
+        }
+    }
+}
+
+// 共有人提案
+#[account]
+pub struct CoOwnerProposal {
+    pub claim: Pubkey,
+    pub claim_sequence: u64, // 提案时确权的回执序号
+    pub index: u64, // 提案编号 (提案时确权的提案计数)
+    pub proposer: Pubkey,
+    pub action: ClaimAction,
+    pub approvals: Vec<Pubkey>,
+    pub executed: bool,
+    pub created_at: i64,
+}
+
+impl CoOwnerProposal {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 32 + (1 + 32) + 4 + ClaimCoOwners::MAX_CO_OWNERS * 32 + 1 + 8;
+    
+    // 提案须属于当前确权记录与当前共有人组合，所有权变更或共有人重设后旧提案作废
+    pub fn is_current(&self, claim: &OwnershipClaim, co_owners: &ClaimCoOwners) -> bool {
+        self.claim_sequence == claim.sequence && self.index >= co_owners.first_proposal
+    }
+}
+
+// 公证人白名单
+#[account]
+pub struct Notary {
+    pub notary: Pubkey,
+    pub added_by: Pubkey,
+    pub added_at: i64,
+    pub total_attestations: u64,
+}
+
+impl Notary {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8;
+}
+
+// 争议仲裁员白名单
+#[account]
+pub struct Arbiter {
+    pub arbiter: Pubkey,
+    pub added_by: Pubkey,
+    pub added_at: i64,
+    pub disputes_resolved: u64,
+}
+
+impl Arbiter {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8;
+}
+
+// 争议状态与裁决结果
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DisputeStatus {
+    Open,
+    Upheld,      // 维持原确权，保证金罚没
+    Transferred, // 确权转给申请人，保证金退还
+    Revoked,     // 撤销确权，保证金退还
+}
+
+// 仲裁员的裁决
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DisputeOutcome {
+    Uphold,
+    Transfer,
+    Revoke,
+}
+
+// 确权争议 (PDA: ["dispute", claim, challenger])
+#[account]
+pub struct Dispute {
+    pub claim: Pubkey,
+    pub challenger: Pubkey,
+    pub bond: u64, // 托管在 escrow 中的 MSC 保证金
+    pub evidence_uri: String,
+    pub evidence_hash: [u8; 32],
+    pub status: DisputeStatus,
+    pub opened_at: i64,
+    pub resolved_by: Option<Pubkey>,
+    pub resolved_at: i64,
+}
+
+impl Dispute {
+    pub const MAX_EVIDENCE_URI_LEN: usize = 200;
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 4 + Self::MAX_EVIDENCE_URI_LEN + 32 + 1 + 8 + (1 + 32) + 8;
+}
+
+// 作品授权要约 (PDA: ["license_offer", claim, offer_id])
+#[account]
+pub struct LicenseOffer {
+    pub claim: Pubkey,
+    pub offer_id: u32,
+    pub price: u64,
+    pub duration: i64, // 授权期限 (秒)，0 表示永久
+    pub transferable: bool,
+    pub max_licenses: u32, // 最大授权数量，0 表示不限
+    pub licenses_sold: u32,
+    pub created_by: Pubkey,
+    pub created_at: i64,
+}
+
+impl LicenseOffer {
+    pub const LEN: usize = 8 + 32 + 4 + 8 + 8 + 1 + 4 + 4 + 32 + 8;
+}
+
+// 作品使用授权 (PDA: ["license", offer, purchaser])
+#[account]
+pub struct License {
+    pub offer: Pubkey,
+    pub claim: Pubkey,
+    pub holder: Pubkey, // 当前被授权人，可转让授权时可变更
+    pub price_paid: u64,
+    pub purchased_at: i64,
+    pub expires_at: i64, // 0 表示永久
+    pub transferable: bool,
+}
+
+impl License {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 8 + 8 + 1;
+    
+    pub fn is_valid(&self, now: i64) -> bool {
+        self.expires_at == 0 || now <= self.expires_at
+    }
+}
+
+// verify_license 通过 return data 返回的验证结果
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LicenseVerification {
+    pub license: Pubkey,
+    pub claim: Pubkey,
+    pub holder: Pubkey,
+    pub expires_at: i64,
+    pub is_valid: bool,
+}
+
+// 挂牌计价币种
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ListingCurrency {
+    Msc,
+    Usdc,
+}
+
+// 确权挂牌 (PDA: ["listing", claim])
+#[account]
+pub struct Listing {
+    pub claim: Pubkey,
+    pub seller: Pubkey,
+    pub price: u64,
+    pub currency: ListingCurrency,
+    pub payment_mint: Pubkey,
+    pub seller_treasury: Option<Pubkey>, // 组织挂牌时售价须划入的组织金库
+    pub allowed_buyers: Vec<Pubkey>, // 买家白名单，为空表示公开出售
+    pub expires_at: i64, // 0 表示不过期
+    pub created_at: i64,
+}
+
+impl Listing {
+    pub const MAX_ALLOWED_BUYERS: usize = 10;
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 1 + 32 + (1 + 32) + (4 + 32 * Self::MAX_ALLOWED_BUYERS) + 8 + 8;
+    
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at != 0 && now > self.expires_at
+    }
+}
+
+// 组织 (PDA: ["organization", name])，组织名即确权命名空间
+#[account]
+pub struct Organization {
+    pub name: String,
+    pub creator: Pubkey,
+    pub treasury: Pubkey, // 组织 MSC 金库 (PDA: ["org_treasury", organization])
+    pub admins: Vec<Pubkey>,
+    pub member_count: u32,
+    pub created_at: i64,
+}
+
+impl Organization {
+    pub const MAX_NAME_LEN: usize = 32;
+    pub const MAX_ADMINS: usize = 5;
+    pub const LEN: usize = 8 + 4 + Self::MAX_NAME_LEN + 32 + 32 + 4 + Self::MAX_ADMINS * 32 + 4 + 8;
+
+    pub fn is_admin(&self, key: &Pubkey) -> bool {
+        self.admins.contains(key)
+    }
+}
+
+// 组织成员 (PDA: ["org_member", organization, member])
+#[account]
+pub struct OrgMember {
+    pub organization: Pubkey,
+    pub member: Pubkey,
+    pub added_by: Pubkey,
+    pub added_at: i64,
+}
+
+impl OrgMember {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8;
+}
+
+// 组织统计 (PDA: ["org_stats", organization])
+#[account]
+pub struct OrgStats {
+    pub organization: Pubkey,
+    pub total_claims: u32,
+    pub total_payments: u64,
+    pub last_activity: i64,
+}
+
+impl OrgStats {
+    pub const LEN: usize = 8 + 32 + 4 + 8 + 8;
+}
+
+// 所有者确权索引头 (PDA: ["owner_index_head", owner])
+// 新确权写入 current_page，该页写满后 current_page 递增，下一页在首次写入时创建
+#[account]
+pub struct OwnerIndexHead {
+    pub owner: Pubkey,
+    pub current_page: u32,
+}
+
+impl OwnerIndexHead {
+    pub const LEN: usize = 8 + 32 + 4;
+}
+
+// 所有者确权索引分页 (PDA: ["owner_index", owner, page])
+#[account]
+pub struct OwnerIndexPage {
+    pub owner: Pubkey,
+    pub page: u32,
+    pub claims: Vec<Pubkey>,
+}
+
+impl OwnerIndexPage {
+    // 整页须能放入 return data (1024 字节)
+    pub const MAX_CLAIMS: usize = 30;
+    pub const LEN: usize = 8 + 32 + 4 + 4 + Self::MAX_CLAIMS * 32;
+}
+
+// get_owner_index_page 通过 return data 返回的索引页
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct OwnerIndexView {
+    pub owner: Pubkey,
+    pub page: u32,
+    pub claims: Vec<Pubkey>,
+}
+
+// 订阅套餐 (PDA: ["plan", plan_id])
+// credits > 0 为确权次数包；credits == 0 为有效期内不限次数
+#[account]
+pub struct Plan {
+    pub plan_id: u16,
+    pub price: u64,
+    pub credits: u32,
+    pub duration: i64, // 不限次数套餐的有效期 (秒)
+    pub is_enabled: bool,
+    pub updated_at: i64,
+}
+
+impl Plan {
+    pub const LEN: usize = 8 + 2 + 8 + 4 + 8 + 1 + 8;
+    
+    pub fn is_unlimited(&self) -> bool {
+        self.credits == 0
+    }
+}
+
+// 用户订阅 (PDA: ["subscription", user])
+#[account]
+pub struct Subscription {
+    pub user: Pubkey,
+    pub credits: u32, // 剩余确权次数
+    pub unlimited_until: i64, // 不限次数截止时间，0 表示未订阅
+    pub credits_used: u64,
+    pub total_paid: u64,
+    pub updated_at: i64,
+}
+
+impl Subscription {
+    pub const LEN: usize = 8 + 32 + 4 + 8 + 8 + 8 + 8;
+    
+    pub fn is_unlimited(&self, now: i64) -> bool {
+        now <= self.unlimited_until
+    }
+}
+
+// 批量确权记录 (Merkle 根锚定多个文件哈希)
+#[account]
+pub struct BatchClaim {
+    pub owner: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub count: u32, // 批次中的文件数量
+    pub depth: u8,  // Merkle 树深度 ceil(log2(count))，即证明的最大长度
+    pub amount_paid: u64,
+    pub timestamp: i64,
+    pub sequence: u64, // 全局回执序号
+    pub slot: u64, // 批次时间戳确定时的 slot
+    pub recent_slot: u64, // 写入时 SlotHashes 最新条目的 slot
+    pub recent_slot_hash: [u8; 32], // 对应的 slot 哈希，证明批次不早于该账本位置
+    pub is_active: bool,
+}
+
+impl BatchClaim {
+    pub const LEN: usize = 8 + 32 + 32 + 4 + 1 + 8 + 8 + 8 + 8 + 8 + 32 + 1;
+    
+    // count 个叶子的 Merkle 树深度
+    pub fn tree_depth(count: u32) -> u8 {
+        if count <= 1 {
+            0
+        } else {
+            (u32::BITS - (count - 1).leading_zeros()) as u8
+        }
+    }
+}
+
+// 压缩确权树配置 (PDA 同时作为 Merkle 树的权限账户)
+#[account]
+pub struct ClaimTree {
+    pub merkle_tree: Pubkey,
+    pub creator: Pubkey,
+    pub max_depth: u32,
+    pub max_buffer_size: u32,
+    pub num_claims: u64, // 已追加的叶子数量，即下一个叶子索引
+    pub bump: u8,
+}
+
+impl ClaimTree {
+    pub const LEN: usize = 8 + 32 + 32 + 4 + 4 + 8 + 1;
+}
+
+// 压缩确权叶子数据 (完整数据通过 noop 程序记录，链上仅存哈希)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct CompressedClaim {
+    pub owner: Pubkey,
+    pub file_hash: String,
+    pub timestamp: i64,
+    pub leaf_index: u32,
+    pub sequence: u64, // 全局回执序号
+    pub slot: u64, // 确权时间戳确定时的 slot
+    pub recent_slot: u64, // 写入时 SlotHashes 最新条目的 slot
+    pub recent_slot_hash: [u8; 32], // 对应的 slot 哈希，证明确权不早于该账本位置
+    pub is_active: bool,
+}
+
+// 压缩确权变更类型
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum CompressedClaimAction {
+    Created,
+    Transferred,
+    Revoked,
+}
+
+// 供索引器解析的压缩确权事件
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CompressedClaimEvent {
+    pub merkle_tree: Pubkey,
+    pub action: CompressedClaimAction,
+    pub claim: CompressedClaim,
+}
+
+// 服务配置 (每个服务一个 PDA，由管理员维护)
+#[account]
+pub struct ServiceConfig {
+    pub service_id: u8,
+    pub price: u64, // MSC 价格 (6 decimals)
+    pub is_enabled: bool,
+    pub treasury: Pubkey, // 收款金库代币账户
+    pub name: String,
+    pub updated_at: i64,
+}
+
+impl ServiceConfig {
+    pub const MAX_NAME_LEN: usize = 32;
+    pub const LEN: usize = 8 + 1 + 8 + 1 + 32 + 4 + Self::MAX_NAME_LEN + 8;
+}
+
+// 确权承诺 (commit-reveal：先提交哈希承诺并付款，再揭示文件哈希)
+#[account]
+pub struct ClaimCommitment {
+    pub owner: Pubkey,
+    pub commitment: [u8; 32], // sha256(file_hash || owner || salt)
+    pub amount_paid: u64,
+    pub payment_sequence: u64, // 提交时分配的支付回执序号
+    pub committed_at: i64, // 揭示后作为确权时间戳
+    pub committed_slot: u64,
+    pub recent_slot: u64, // 提交时 SlotHashes 最新条目，揭示后作为确权账本锚点
+    pub recent_slot_hash: [u8; 32],
+}
+
+impl ClaimCommitment {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 32;
 }
 
 // 服务支付记录
@@ -34,13 +600,21 @@ pub struct PaymentRecord {
     pub payer: Pubkey,
     pub amount: u64,
     pub timestamp: i64,
+    pub sequence: u64, // 全局回执序号
     pub transaction_id: String,
     pub status: u8, // 0: 待处理, 1: 已完成, 2: 已退款
     pub is_used: bool, // 防止重复使用付费记录
+    pub service_id: u8,
+    pub refundable_after: i64, // 待处理支付可由付款人申请退款的时间
+    pub certificate_amount: u64, // 同时铸造确权证书时支付给证书服务金库的费用，不计入 amount
 }
 
 impl PaymentRecord {
-    pub const LEN: usize = 8 + 32 + 8 + 8 + 4 + 64 + 1 + 1;
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 4 + 64 + 1 + 1 + 1 + 8 + 8;
+    
+    pub const STATUS_PENDING: u8 = 0;
+    pub const STATUS_COMPLETED: u8 = 1;
+    pub const STATUS_REFUNDED: u8 = 2;
 }
 
 
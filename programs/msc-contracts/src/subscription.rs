@@ -0,0 +1,259 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::state::*;
+use crate::error::*;
+use crate::service::{charge_service_fee, validate_payment_accounts, SERVICE_CLAIM};
+
+// 创建订阅套餐 (仅限管理员)
+pub fn create_plan(
+    ctx: Context<CreatePlan>,
+    plan_id: u16,
+    price: u64,
+    credits: u32,
+    duration: i64,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    require!(config.is_initialized, MscError::AccountNotInitialized);
+    require!(ctx.accounts.authority.key() == config.authority, MscError::InvalidAuthority);
+    validate_plan_terms(credits, duration)?;
+    
+    let plan = &mut ctx.accounts.plan;
+    plan.plan_id = plan_id;
+    plan.price = price;
+    plan.credits = credits;
+    plan.duration = duration;
+    plan.is_enabled = true;
+    plan.updated_at = Clock::get()?.unix_timestamp;
+    
+    msg!("Plan created:");
+    msg!("Plan ID: {}", plan_id);
+    msg!("Price: {} MSC", price);
+    msg!("Credits: {}", credits);
+    msg!("Duration: {}", duration);
+    
+    Ok(())
+}
+
+// 更新订阅套餐 (仅限管理员)，已购买的订阅不受影响
+pub fn update_plan(
+    ctx: Context<UpdatePlan>,
+    price: u64,
+    credits: u32,
+    duration: i64,
+    is_enabled: bool,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    require!(config.is_initialized, MscError::AccountNotInitialized);
+    require!(ctx.accounts.authority.key() == config.authority, MscError::InvalidAuthority);
+    validate_plan_terms(credits, duration)?;
+    
+    let plan = &mut ctx.accounts.plan;
+    plan.price = price;
+    plan.credits = credits;
+    plan.duration = duration;
+    plan.is_enabled = is_enabled;
+    plan.updated_at = Clock::get()?.unix_timestamp;
+    
+    msg!("Plan updated:");
+    msg!("Plan ID: {}", plan.plan_id);
+    msg!("Price: {} MSC", price);
+    msg!("Enabled: {}", is_enabled);
+    
+    Ok(())
+}
+
+// 创建用户订阅账户 (购买套餐前调用一次)
+pub fn create_subscription(ctx: Context<CreateSubscription>) -> Result<()> {
+    let subscription = &mut ctx.accounts.subscription;
+    subscription.user = ctx.accounts.user.key();
+    subscription.credits = 0;
+    subscription.unlimited_until = 0;
+    subscription.credits_used = 0;
+    subscription.total_paid = 0;
+    subscription.updated_at = Clock::get()?.unix_timestamp;
+    
+    msg!("Subscription created for: {}", subscription.user);
+    
+    Ok(())
+}
+
+// 购买套餐：次数包累加剩余次数，不限次数套餐从当前截止时间 (或现在) 顺延
+// 款项划入确权服务金库
+pub fn purchase_plan(
+    ctx: Context<PurchasePlan>,
+    max_price: u64,
+) -> Result<()> {
+    let plan = &ctx.accounts.plan;
+    require!(plan.is_enabled, MscError::ServiceDisabled);
+    require!(plan.price <= max_price, MscError::PriceAboveMaximum);
+    
+    validate_payment_accounts(
+        &ctx.accounts.config,
+        &ctx.accounts.service_config,
+        &ctx.accounts.user_token_account,
+        &ctx.accounts.treasury_token_account,
+        ctx.accounts.user.key(),
+    )?;
+    charge_service_fee(
+        &ctx.accounts.token_program,
+        &ctx.accounts.user_token_account,
+        &ctx.accounts.treasury_token_account,
+        ctx.accounts.user.to_account_info(),
+        plan.price,
+    )?;
+    
+    let clock = Clock::get()?;
+    let subscription = &mut ctx.accounts.subscription;
+    if plan.is_unlimited() {
+        let start = subscription.unlimited_until.max(clock.unix_timestamp);
+        subscription.unlimited_until = start.checked_add(plan.duration).ok_or(MscError::MathOverflow)?;
+    } else {
+        subscription.credits = subscription.credits.checked_add(plan.credits).ok_or(MscError::MathOverflow)?;
+    }
+    subscription.total_paid = subscription.total_paid.checked_add(plan.price).ok_or(MscError::MathOverflow)?;
+    subscription.updated_at = clock.unix_timestamp;
+    
+    msg!("Plan purchased:");
+    msg!("User: {}", subscription.user);
+    msg!("Plan ID: {}", plan.plan_id);
+    msg!("Amount: {} MSC", plan.price);
+    msg!("Credits: {}", subscription.credits);
+    msg!("Unlimited Until: {}", subscription.unlimited_until);
+    
+    Ok(())
+}
+
+// 辅助函数：为 count 条确权扣减订阅额度，不限次数有效期内不消耗次数
+pub fn draw_claim_credits(subscription: &mut Subscription, count: u32, now: i64) -> Result<()> {
+    if !subscription.is_unlimited(now) {
+        require!(subscription.credits >= count, MscError::NoClaimCredits);
+        subscription.credits -= count;
+    }
+    subscription.credits_used = subscription.credits_used.checked_add(u64::from(count)).ok_or(MscError::MathOverflow)?;
+    subscription.updated_at = now;
+    Ok(())
+}
+
+// 辅助函数：确定 count 条确权应付的确权费用
+// 传入订阅账户时扣减订阅额度并免收确权费用，否则按 price 收费
+pub fn apply_claim_credits(
+    price: u64,
+    subscription: Option<&mut Subscription>,
+    count: u32,
+    now: i64,
+) -> Result<u64> {
+    match subscription {
+        Some(subscription) => {
+            draw_claim_credits(subscription, count, now)?;
+            Ok(0)
+        }
+        None => Ok(price),
+    }
+}
+
+// 辅助函数：套餐须为次数包或有有效期的不限次数套餐
+fn validate_plan_terms(credits: u32, duration: i64) -> Result<()> {
+    require!(duration >= 0, MscError::InvalidPlan);
+    require!(credits > 0 || duration > 0, MscError::InvalidPlan);
+    Ok(())
+}
+
+// Account 结构定义
+
+#[derive(Accounts)]
+#[instruction(plan_id: u16)]
+pub struct CreatePlan<'info> {
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Account<'info, MscTokenConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = Plan::LEN,
+        seeds = [b"plan", plan_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub plan: Account<'info, Plan>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePlan<'info> {
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Account<'info, MscTokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"plan", plan.plan_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub plan: Account<'info, Plan>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateSubscription<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = Subscription::LEN,
+        seeds = [b"subscription", user.key().as_ref()],
+        bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PurchasePlan<'info> {
+    #[account(
+        seeds = [b"plan", plan.plan_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub plan: Account<'info, Plan>,
+    
+    #[account(
+        mut,
+        seeds = [b"subscription", user.key().as_ref()],
+        bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+    
+    // 套餐款项划入确权服务金库
+    #[account(
+        seeds = [b"service_config", [SERVICE_CLAIM].as_ref()],
+        bump
+    )]
+    pub service_config: Box<Account<'info, ServiceConfig>>,
+    
+    #[account(
+        seeds = [b"msc_config"],
+        bump
+    )]
+    pub config: Box<Account<'info, MscTokenConfig>>,
+    
+    #[account(mut)]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    
+    pub user: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
@@ -1,6 +1,6 @@
 const anchor = require("@coral-xyz/anchor");
 const { SystemProgram, Keypair, LAMPORTS_PER_SOL } = anchor.web3;
-const { TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID, createMint, createAccount, mintTo, getAccount, getAssociatedTokenAddress, createAssociatedTokenAccount } = require("@solana/spl-token");
+const { TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID, createMint, createAccount, mintTo, getAccount, getAssociatedTokenAddress, createAssociatedTokenAccount, setAuthority, AuthorityType } = require("@solana/spl-token");
 const assert = require("assert");
 
 describe("MSC Contracts", () => {
@@ -20,6 +20,90 @@ describe("MSC Contracts", () => {
   let mscTokenConfig;
   let exchangePool;
   
+  const globalStatePda = () =>
+    anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("global_state")], program.programId)[0];
+  const serviceConfigPda = (serviceId) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("service_config"), Buffer.from([serviceId])],
+      program.programId
+    )[0];
+  const ownerIndexHeadPda = (owner) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("owner_index_head"), owner.toBuffer()],
+      program.programId
+    )[0];
+  const ownerIndexPda = (owner, page = 0) => {
+    const pageBytes = Buffer.alloc(4);
+    pageBytes.writeUInt32LE(page);
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("owner_index"), owner.toBuffer(), pageBytes],
+      program.programId
+    )[0];
+  };
+  
+  const claimPdaFor = (seed, owner, fileHash) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(seed), owner.toBuffer(), Buffer.from(fileHash)],
+      program.programId
+    )[0];
+  
+  // 断言交易失败且错误信息包含指定错误码
+  const expectError = async (promise, code) => {
+    try {
+      await promise;
+    } catch (error) {
+      assert.ok(error.toString().includes(code), `expected ${code}, got ${error}`);
+      return;
+    }
+    assert.fail(`expected ${code}`);
+  };
+  
+  // 新建持有 SOL 与 MSC 的测试用户
+  const createFundedUser = async (mscAmount = 100000000) => {
+    const keypair = Keypair.generate();
+    const signature = await connection.requestAirdrop(keypair.publicKey, 2 * LAMPORTS_PER_SOL);
+    await connection.confirmTransaction(signature);
+    const tokenAccount = await createAssociatedTokenAccount(connection, keypair, mscMint, keypair.publicKey);
+    await mintTo(connection, authority, mscMint, tokenAccount, authority, mscAmount);
+    return keypair;
+  };
+  
+  // pay_and_create_claim 的账户 (不铸造证书)
+  const payAndCreateClaimAccounts = async (owner, fileHash, overrides = {}) => {
+    const serviceConfig = await program.account.serviceConfig.fetch(serviceConfigPda(0));
+    return {
+      paymentRecord: claimPdaFor("payment", owner, fileHash),
+      claim: claimPdaFor("claim", owner, fileHash),
+      config: mscTokenConfig,
+      serviceConfig: serviceConfigPda(0),
+      globalState: globalStatePda(),
+      userStats: anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("user_stats"), owner.toBuffer()],
+        program.programId
+      )[0],
+      slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+      ownerIndexHead: ownerIndexHeadPda(owner),
+      ownerIndex: ownerIndexPda(owner),
+      subscription: null,
+      parentClaim: null,
+      userTokenAccount: await getAssociatedTokenAddress(mscMint, owner),
+      treasuryTokenAccount: serviceConfig.treasury,
+      user: owner,
+      certificateMint: null,
+      certificateTokenAccount: null,
+      certificateMetadata: null,
+      certificateAuthority: null,
+      certificateServiceConfig: null,
+      certificateTreasuryTokenAccount: null,
+      tokenMetadataProgram: null,
+      associatedTokenProgram: null,
+      rent: null,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+      ...overrides,
+    };
+  };
+  
   before(async () => {
     // Create test keypairs
     authority = Keypair.generate();
@@ -109,6 +193,7 @@ describe("MSC Contracts", () => {
         const tx = await program.methods
           .transferMsc(new anchor.BN(transferAmount))
           .accounts({
+            config: mscTokenConfig,
             from: authorityTokenAccount,
             to: userTokenAccount,
             authority: authority.publicKey,
@@ -129,6 +214,218 @@ describe("MSC Contracts", () => {
     });
   });
   
+  describe("Global State Tests", () => {
+    it("Initialize receipt counter", async () => {
+      await program.methods
+        .initializeGlobalState()
+        .accounts({
+          config: mscTokenConfig,
+          serviceConfig: serviceConfigPda(0),
+          globalState: globalStatePda(),
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+      
+      const globalState = await program.account.globalState.fetch(globalStatePda());
+      assert.equal(globalState.receiptCounter.toString(), "0");
+    });
+  });
+  
+  describe("Service Config Tests", () => {
+    it("Initialize claim and certificate service configs", async () => {
+      const treasury = await getAssociatedTokenAddress(mscMint, authority.publicKey);
+      
+      for (const [serviceId, price, name] of [
+        [0, 1000000, "Claim"],
+        [1, 1000000, "Renewal"],
+        [2, 500000, "Certificate"],
+        [4, 2000000, "Dispute"],
+      ]) {
+        await program.methods
+          .initializeServiceConfig(serviceId, new anchor.BN(price), treasury, name)
+          .accounts({
+            config: mscTokenConfig,
+            serviceConfig: serviceConfigPda(serviceId),
+            authority: authority.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority])
+          .rpc();
+      }
+      
+      const claimService = await program.account.serviceConfig.fetch(serviceConfigPda(0));
+      assert.equal(claimService.price.toString(), "1000000");
+      assert.equal(claimService.isEnabled, true);
+    });
+    
+    it("Reject unknown service id", async () => {
+      await expectError(
+        program.methods
+          .initializeServiceConfig(9, new anchor.BN(1), authority.publicKey, "Unknown")
+          .accounts({
+            config: mscTokenConfig,
+            serviceConfig: serviceConfigPda(9),
+            authority: authority.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority])
+          .rpc(),
+        "InvalidServiceType"
+      );
+    });
+    
+    it("Reject claim when price exceeds max_price", async () => {
+      const fileHash = "0f".repeat(32);
+      
+      await expectError(
+        program.methods
+          .payAndCreateClaim(new anchor.BN(999999), fileHash, { original: {} })
+          .accounts(await payAndCreateClaimAccounts(user.publicKey, fileHash))
+          .signers([user])
+          .rpc(),
+        "PriceAboveMaximum"
+      );
+    });
+    
+    it("Reject claim fee sent to an unconfigured treasury", async () => {
+      const fileHash = "1f".repeat(32);
+      const userTokenAccount = await getAssociatedTokenAddress(mscMint, user.publicKey);
+      
+      await expectError(
+        program.methods
+          .payAndCreateClaim(new anchor.BN(1000000), fileHash, { original: {} })
+          .accounts(await payAndCreateClaimAccounts(user.publicKey, fileHash, { treasuryTokenAccount: userTokenAccount }))
+          .signers([user])
+          .rpc(),
+        "InvalidTreasury"
+      );
+    });
+  });
+  
+  describe("Subscription Tests", () => {
+    const findPda = (seeds) => anchor.web3.PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+    const planPda = (planId) => {
+      const planIdBytes = Buffer.alloc(2);
+      planIdBytes.writeUInt16LE(planId);
+      return findPda([Buffer.from("plan"), planIdBytes]);
+    };
+    
+    it("Reject a plan with neither credits nor duration", async () => {
+      await expectError(
+        program.methods
+          .createPlan(9, new anchor.BN(1000000), 0, new anchor.BN(0))
+          .accounts({
+            config: mscTokenConfig,
+            plan: planPda(9),
+            authority: authority.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority])
+          .rpc(),
+        "InvalidPlan"
+      );
+    });
+    
+    it("Purchase a credit pack into a subscription", async () => {
+      const subscription = findPda([Buffer.from("subscription"), user.publicKey.toBuffer()]);
+      
+      await program.methods
+        .createPlan(1, new anchor.BN(8000000), 10, new anchor.BN(0))
+        .accounts({
+          config: mscTokenConfig,
+          plan: planPda(1),
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+      
+      await program.methods
+        .createSubscription()
+        .accounts({
+          subscription,
+          user: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      
+      await program.methods
+        .purchasePlan(new anchor.BN(8000000))
+        .accounts({
+          plan: planPda(1),
+          subscription,
+          serviceConfig: serviceConfigPda(0),
+          config: mscTokenConfig,
+          userTokenAccount: await getAssociatedTokenAddress(mscMint, user.publicKey),
+          treasuryTokenAccount: await getAssociatedTokenAddress(mscMint, authority.publicKey),
+          user: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+      
+      const account = await program.account.subscription.fetch(subscription);
+      assert.equal(account.credits, 10);
+      assert.equal(account.totalPaid.toString(), "8000000");
+    });
+    
+    it("Draw one credit per claim for repeated claims by the same user", async () => {
+      const subscriber = await createFundedUser();
+      const subscription = findPda([Buffer.from("subscription"), subscriber.publicKey.toBuffer()]);
+      
+      await program.methods
+        .createSubscription()
+        .accounts({
+          subscription,
+          user: subscriber.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([subscriber])
+        .rpc();
+      
+      await program.methods
+        .purchasePlan(new anchor.BN(8000000))
+        .accounts({
+          plan: planPda(1),
+          subscription,
+          serviceConfig: serviceConfigPda(0),
+          config: mscTokenConfig,
+          userTokenAccount: await getAssociatedTokenAddress(mscMint, subscriber.publicKey),
+          treasuryTokenAccount: (await program.account.serviceConfig.fetch(serviceConfigPda(0))).treasury,
+          user: subscriber.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([subscriber])
+        .rpc();
+      
+      // 同一用户的第二次确权复用已存在的 user_stats，并再扣减一次额度
+      const fileHashes = [
+        "5ab5cafe00000000000000000000000000000000000000000000000000000001",
+        "5ab5cafe00000000000000000000000000000000000000000000000000000002",
+      ];
+      for (const fileHash of fileHashes) {
+        await program.methods
+          .payAndCreateClaim(new anchor.BN(0), fileHash, { original: {} })
+          .accounts(await payAndCreateClaimAccounts(subscriber.publicKey, fileHash, { subscription }))
+          .signers([subscriber])
+          .rpc();
+      }
+      
+      const account = await program.account.subscription.fetch(subscription);
+      assert.equal(account.credits, 8);
+      assert.equal(account.creditsUsed.toString(), "2");
+      
+      const stats = await program.account.userStats.fetch(
+        findPda([Buffer.from("user_stats"), subscriber.publicKey.toBuffer()])
+      );
+      assert.equal(stats.totalClaims, 2);
+      assert.equal(stats.totalPayments.toString(), "0");
+    });
+  });
+  
   describe("Ownership Contract Tests", () => {
     it("Create Ownership Claim", async () => {
       const fileHash = "a1b2c3d4e5f6789012345678901234567890123456789012345678901234567890";
@@ -163,6 +460,1336 @@ describe("MSC Contracts", () => {
     });
   });
   
+  describe("Claim Verification Tests", () => {
+    it("Verify claim without owner signature", async () => {
+      const fileHash = "a1b2c3d4e5f67890123456789012345678901234567890123456789012345678";
+      const claimPda = claimPdaFor("claim", user.publicKey, fileHash);
+      
+      // 后续元数据、挂牌与共有确权测试均使用该确权记录
+      await program.methods
+        .payAndCreateClaim(new anchor.BN(1000000), fileHash, { original: {} })
+        .accounts(await payAndCreateClaimAccounts(user.publicKey, fileHash))
+        .signers([user])
+        .rpc();
+      
+      const verification = await program.methods
+        .verifyClaim(fileHash)
+        .accounts({ claim: claimPda })
+        .view();
+      
+      assert.equal(verification.owner.toString(), user.publicKey.toString());
+      assert.ok(verification.status.active);
+      assert.ok(verification.algorithm.sha256);
+    });
+  });
+  
+  describe("Claim Certificate Tests", () => {
+    const TOKEN_METADATA_PROGRAM_ID = new anchor.web3.PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+    const fileHash = "a2b2c3d4e5f67890123456789012345678901234567890123456789012345678";
+    const certificateMint = Keypair.generate();
+    
+    it("Mint certificate NFT with claim", async () => {
+      const [certificateAuthority] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("certificate_authority")],
+        program.programId
+      );
+      const [certificateMetadata] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), certificateMint.publicKey.toBuffer()],
+        TOKEN_METADATA_PROGRAM_ID
+      );
+      
+      await program.methods
+        .payAndCreateClaim(new anchor.BN(1000000), fileHash, { original: {} })
+        .accounts(await payAndCreateClaimAccounts(user.publicKey, fileHash, {
+          certificateMint: certificateMint.publicKey,
+          certificateTokenAccount: await getAssociatedTokenAddress(certificateMint.publicKey, user.publicKey),
+          certificateMetadata,
+          certificateAuthority,
+          certificateServiceConfig: serviceConfigPda(2),
+          certificateTreasuryTokenAccount: (await program.account.serviceConfig.fetch(serviceConfigPda(2))).treasury,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        }))
+        .signers([user, certificateMint])
+        .rpc();
+      
+      const claim = await program.account.ownershipClaim.fetch(claimPdaFor("claim", user.publicKey, fileHash));
+      assert.equal(claim.certificateMint.toString(), certificateMint.publicKey.toString());
+      const payment = await program.account.paymentRecord.fetch(claimPdaFor("payment", user.publicKey, fileHash));
+      const certificateConfig = await program.account.serviceConfig.fetch(serviceConfigPda(2));
+      assert.equal(payment.certificateAmount.toString(), certificateConfig.price.toString());
+      assert.equal(claim.indexPage, 0);
+      assert.ok(claim.recentSlot.lte(claim.slot));
+      
+      const page = await program.methods
+        .getOwnerIndexPage()
+        .accounts({ ownerIndex: ownerIndexPda(user.publicKey) })
+        .view();
+      assert.ok(page.claims.some((key) => key.equals(claimPdaFor("claim", user.publicKey, fileHash))));
+    });
+    
+    it("Sync claim owner from certificate holder", async () => {
+      await program.methods
+        .syncClaimOwner()
+        .accounts({
+          claim: claimPdaFor("claim", user.publicKey, fileHash),
+          certificateTokenAccount: await getAssociatedTokenAddress(certificateMint.publicKey, user.publicKey),
+          fromOwnerIndex: null,
+          toOwnerIndexHead: ownerIndexHeadPda(user.publicKey),
+          toOwnerIndex: ownerIndexPda(user.publicKey),
+          payer: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      
+      const claim = await program.account.ownershipClaim.fetch(claimPdaFor("claim", user.publicKey, fileHash));
+      assert.equal(claim.owner.toString(), user.publicKey.toString());
+    });
+  });
+  
+  describe("Claim Expiry Tests", () => {
+    const fileHash = "a2b2c3d4e5f67890123456789012345678901234567890123456789012345678";
+    it("Renew claim extends expiry by one term", async () => {
+      const before = await program.account.ownershipClaim.fetch(claimPdaFor("claim", user.publicKey, fileHash));
+      
+      await program.methods
+        .renewClaim(new anchor.BN(1000000))
+        .accounts({
+          organization: null,
+          claim: claimPdaFor("claim", user.publicKey, fileHash),
+          serviceConfig: serviceConfigPda(1),
+          config: mscTokenConfig,
+          ownerTokenAccount: await getAssociatedTokenAddress(mscMint, user.publicKey),
+          treasuryTokenAccount: await getAssociatedTokenAddress(mscMint, authority.publicKey),
+          owner: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+      
+      const after = await program.account.ownershipClaim.fetch(claimPdaFor("claim", user.publicKey, fileHash));
+      assert.equal(after.expiresAt.sub(before.expiresAt).toNumber(), 365 * 24 * 60 * 60);
+    });
+    
+    it("Reject closing a claim before its grace period ends", async () => {
+      await expectError(
+        program.methods
+          .closeExpiredClaim()
+          .accounts({
+            claim: claimPdaFor("claim", user.publicKey, fileHash),
+            paymentRecord: claimPdaFor("payment", user.publicKey, fileHash),
+            claimMetadata: anchor.web3.PublicKey.findProgramAddressSync(
+              [Buffer.from("claim_metadata"), claimPdaFor("claim", user.publicKey, fileHash).toBuffer()],
+              program.programId
+            )[0],
+            coOwners: null,
+            ownerIndex: ownerIndexPda(user.publicKey),
+            creator: user.publicKey,
+          })
+          .rpc(),
+        "ClaimNotExpired"
+      );
+    });
+  });
+  
+  describe("Dispute Tests", () => {
+    const fileHash = "a2b2c3d4e5f67890123456789012345678901234567890123456789012345678";
+    const evidenceHash = Array.from(Buffer.alloc(32, 7));
+    const findPda = (seeds) => anchor.web3.PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+    
+    const disputeAccounts = async (challenger) => {
+      const claim = findPda([Buffer.from("claim"), user.publicKey.toBuffer(), Buffer.from(fileHash)]);
+      const dispute = findPda([Buffer.from("dispute"), claim.toBuffer(), challenger.publicKey.toBuffer()]);
+      return {
+        dispute,
+        claim,
+        serviceConfig: serviceConfigPda(4),
+        config: mscTokenConfig,
+        escrowTokenAccount: findPda([Buffer.from("dispute_escrow"), dispute.toBuffer()]),
+        escrowAuthority: findPda([Buffer.from("escrow_authority")]),
+        mint: mscMint,
+        challengerTokenAccount: await getAssociatedTokenAddress(mscMint, challenger.publicKey),
+        challenger: challenger.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      };
+    };
+    
+    it("Reject a dispute opened by the claim owner", async () => {
+      await expectError(
+        program.methods
+          .openDispute("ipfs://evidence", evidenceHash, new anchor.BN(2000000))
+          .accounts(await disputeAccounts(user))
+          .signers([user])
+          .rpc(),
+        "SelfDispute"
+      );
+    });
+    
+    it("Open a dispute and resolve it by upholding the claim", async () => {
+      const arbiterEntry = findPda([Buffer.from("arbiter"), authority.publicKey.toBuffer()]);
+      
+      await program.methods
+        .addArbiter(authority.publicKey)
+        .accounts({
+          config: mscTokenConfig,
+          arbiterEntry,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+      
+      const accounts = await disputeAccounts(authority);
+      await program.methods
+        .openDispute("ipfs://evidence", evidenceHash, new anchor.BN(2000000))
+        .accounts(accounts)
+        .signers([authority])
+        .rpc();
+      
+      const disputed = await program.methods
+        .verifyClaim(fileHash)
+        .accounts({ claim: accounts.claim })
+        .view();
+      assert.ok(disputed.status.disputed);
+      
+      await program.methods
+        .resolveDispute({ uphold: {} })
+        .accounts({
+          dispute: accounts.dispute,
+          claim: accounts.claim,
+          arbiterEntry,
+          serviceConfig: serviceConfigPda(4),
+          escrowTokenAccount: accounts.escrowTokenAccount,
+          escrowAuthority: accounts.escrowAuthority,
+          treasuryTokenAccount: await getAssociatedTokenAddress(mscMint, authority.publicKey),
+          challengerTokenAccount: accounts.challengerTokenAccount,
+          challenger: authority.publicKey,
+          listing: null,
+          seller: null,
+          fromOwnerIndex: null,
+          toOwnerIndexHead: null,
+          toOwnerIndex: null,
+          arbiter: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+      
+      const dispute = await program.account.dispute.fetch(accounts.dispute);
+      assert.ok(dispute.status.upheld);
+      const claim = await program.account.ownershipClaim.fetch(accounts.claim);
+      assert.equal(claim.isDisputed, false);
+    });
+    
+    it("Close the listing when a dispute revokes a listed claim", async () => {
+      const listedHash = "a3b2c3d4e5f67890123456789012345678901234567890123456789012345678";
+      const owner = await createFundedUser();
+      const claim = claimPdaFor("claim", owner.publicKey, listedHash);
+      const listing = findPda([Buffer.from("listing"), claim.toBuffer()]);
+      const dispute = findPda([Buffer.from("dispute"), claim.toBuffer(), authority.publicKey.toBuffer()]);
+      const escrowTokenAccount = findPda([Buffer.from("dispute_escrow"), dispute.toBuffer()]);
+      const escrowAuthority = findPda([Buffer.from("escrow_authority")]);
+      const challengerTokenAccount = await getAssociatedTokenAddress(mscMint, authority.publicKey);
+      
+      await program.methods
+        .payAndCreateClaim(new anchor.BN(100000000), listedHash, { original: {} })
+        .accounts(await payAndCreateClaimAccounts(owner.publicKey, listedHash))
+        .signers([owner])
+        .rpc();
+      
+      await program.methods
+        .listClaim(new anchor.BN(5000000), { msc: {} }, new anchor.BN(0), [])
+        .accounts({
+          organization: null,
+          listing,
+          claim,
+          config: mscTokenConfig,
+          exchangePool: null,
+          owner: owner.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+      
+      await program.methods
+        .openDispute("ipfs://evidence", evidenceHash, new anchor.BN(2000000))
+        .accounts({
+          dispute,
+          claim,
+          serviceConfig: serviceConfigPda(4),
+          config: mscTokenConfig,
+          escrowTokenAccount,
+          escrowAuthority,
+          mint: mscMint,
+          challengerTokenAccount,
+          challenger: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+      
+      const resolveAccounts = {
+        dispute,
+        claim,
+        arbiterEntry: findPda([Buffer.from("arbiter"), authority.publicKey.toBuffer()]),
+        serviceConfig: serviceConfigPda(4),
+        escrowTokenAccount,
+        escrowAuthority,
+        treasuryTokenAccount: challengerTokenAccount,
+        challengerTokenAccount,
+        challenger: authority.publicKey,
+        fromOwnerIndex: null,
+        toOwnerIndexHead: null,
+        toOwnerIndex: null,
+        arbiter: authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      };
+      
+      await expectError(
+        program.methods
+          .resolveDispute({ revoke: {} })
+          .accounts({ ...resolveAccounts, listing: null, seller: null })
+          .signers([authority])
+          .rpc(),
+        "ListingRequired"
+      );
+      
+      await program.methods
+        .resolveDispute({ revoke: {} })
+        .accounts({ ...resolveAccounts, listing, seller: owner.publicKey })
+        .signers([authority])
+        .rpc();
+      
+      assert.equal(await connection.getAccountInfo(listing), null);
+      const revoked = await program.account.ownershipClaim.fetch(claim);
+      assert.equal(revoked.isActive, false);
+      assert.equal(revoked.isListed, false);
+    });
+  });
+  
+  describe("Licensing Tests", () => {
+    const fileHash = "a2b2c3d4e5f67890123456789012345678901234567890123456789012345678";
+    const findPda = (seeds) => anchor.web3.PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+    
+    it("Create offer, purchase and verify a license", async () => {
+      const claim = findPda([Buffer.from("claim"), user.publicKey.toBuffer(), Buffer.from(fileHash)]);
+      const offerId = 1;
+      const offerIdBytes = Buffer.alloc(4);
+      offerIdBytes.writeUInt32LE(offerId);
+      const licenseOffer = findPda([Buffer.from("license_offer"), claim.toBuffer(), offerIdBytes]);
+      const license = findPda([Buffer.from("license"), licenseOffer.toBuffer(), authority.publicKey.toBuffer()]);
+      
+      await program.methods
+        .createLicenseOffer(offerId, new anchor.BN(4000000), new anchor.BN(30 * 24 * 60 * 60), true, 10)
+        .accounts({
+          organization: null,
+          licenseOffer,
+          claim,
+          coOwners: null,
+          owner: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      
+      await program.methods
+        .purchaseLicense()
+        .accounts({
+          license,
+          licenseOffer,
+          claim,
+          coOwners: null,
+          organization: null,
+          config: mscTokenConfig,
+          serviceConfig: serviceConfigPda(0),
+          buyerTokenAccount: await getAssociatedTokenAddress(mscMint, authority.publicKey),
+          treasuryTokenAccount: await getAssociatedTokenAddress(mscMint, authority.publicKey),
+          ownerTokenAccount: await getAssociatedTokenAddress(mscMint, user.publicKey),
+          buyer: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+      
+      const offer = await program.account.licenseOffer.fetch(licenseOffer);
+      assert.equal(offer.licensesSold, 1);
+      
+      const verification = await program.methods
+        .verifyLicense(authority.publicKey)
+        .accounts({ license, claim })
+        .view();
+      assert.ok(verification.isValid);
+    });
+    
+    it("Reject purchases of offers made by a previous owner", async () => {
+      const offerFileHash = "a4b2c3d4e5f67890123456789012345678901234567890123456789012345678";
+      const seller = await createFundedUser();
+      const buyer = await createFundedUser();
+      const recipient = Keypair.generate();
+      const claim = claimPdaFor("claim", seller.publicKey, offerFileHash);
+      const offerIdBytes = Buffer.alloc(4);
+      const licenseOffer = findPda([Buffer.from("license_offer"), claim.toBuffer(), offerIdBytes]);
+      
+      await program.methods
+        .payAndCreateClaim(new anchor.BN(100000000), offerFileHash, { original: {} })
+        .accounts(await payAndCreateClaimAccounts(seller.publicKey, offerFileHash))
+        .signers([seller])
+        .rpc();
+      
+      await program.methods
+        .createLicenseOffer(0, new anchor.BN(4000000), new anchor.BN(0), false, 0)
+        .accounts({
+          organization: null,
+          licenseOffer,
+          claim,
+          coOwners: null,
+          owner: seller.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([seller])
+        .rpc();
+      
+      await program.methods
+        .transferClaim(recipient.publicKey)
+        .accounts({
+          organization: null,
+          claim,
+          fromOwnerIndex: ownerIndexPda(seller.publicKey),
+          toOwnerIndexHead: ownerIndexHeadPda(recipient.publicKey),
+          toOwnerIndex: ownerIndexPda(recipient.publicKey),
+          owner: seller.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([seller])
+        .rpc();
+      
+      await expectError(
+        program.methods
+          .purchaseLicense()
+          .accounts({
+            license: findPda([Buffer.from("license"), licenseOffer.toBuffer(), buyer.publicKey.toBuffer()]),
+            licenseOffer,
+            claim,
+            coOwners: null,
+            organization: null,
+            config: mscTokenConfig,
+            serviceConfig: serviceConfigPda(0),
+            buyerTokenAccount: await getAssociatedTokenAddress(mscMint, buyer.publicKey),
+            treasuryTokenAccount: await getAssociatedTokenAddress(mscMint, authority.publicKey),
+            ownerTokenAccount: await getAssociatedTokenAddress(mscMint, seller.publicKey),
+            buyer: buyer.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([buyer])
+          .rpc(),
+        "LicenseOfferInvalidated"
+      );
+    });
+  });
+  
+  describe("Marketplace Tests", () => {
+    const findPda = (seeds) => anchor.web3.PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+    const claimPda = (fileHash) =>
+      findPda([Buffer.from("claim"), user.publicKey.toBuffer(), Buffer.from(fileHash)]);
+    
+    it("Reject listing a claim that carries a certificate", async () => {
+      const claim = claimPda("a2b2c3d4e5f67890123456789012345678901234567890123456789012345678");
+      
+      await expectError(
+        program.methods
+          .listClaim(new anchor.BN(5000000), { msc: {} }, new anchor.BN(0), [])
+          .accounts({
+            organization: null,
+            listing: findPda([Buffer.from("listing"), claim.toBuffer()]),
+            claim,
+            config: mscTokenConfig,
+            exchangePool: null,
+            owner: user.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([user])
+          .rpc(),
+        "ClaimHasCertificate"
+      );
+    });
+    
+    it("List a claim privately, reject other buyers and cancel", async () => {
+      const claim = claimPda("a1b2c3d4e5f67890123456789012345678901234567890123456789012345678");
+      const listing = findPda([Buffer.from("listing"), claim.toBuffer()]);
+      
+      await program.methods
+        .listClaim(new anchor.BN(5000000), { msc: {} }, new anchor.BN(0), [Keypair.generate().publicKey])
+        .accounts({
+          organization: null,
+          listing,
+          claim,
+          config: mscTokenConfig,
+          exchangePool: null,
+          owner: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      
+      const listed = await program.account.ownershipClaim.fetch(claim);
+      assert.equal(listed.isListed, true);
+      
+      await expectError(
+        program.methods
+          .purchaseListing()
+          .accounts({
+            listing,
+            claim,
+            serviceConfig: serviceConfigPda(0),
+            buyerTokenAccount: await getAssociatedTokenAddress(mscMint, authority.publicKey),
+            sellerTokenAccount: await getAssociatedTokenAddress(mscMint, user.publicKey),
+            treasuryTokenAccount: await getAssociatedTokenAddress(mscMint, authority.publicKey),
+            seller: user.publicKey,
+            fromOwnerIndex: null,
+            toOwnerIndexHead: ownerIndexHeadPda(authority.publicKey),
+            toOwnerIndex: ownerIndexPda(authority.publicKey),
+            buyer: authority.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority])
+          .rpc(),
+        "BuyerNotAllowed"
+      );
+      
+      await program.methods
+        .cancelListing()
+        .accounts({
+          organization: null,
+          listing,
+          claim,
+          seller: user.publicKey,
+          signer: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+      
+      const cancelled = await program.account.ownershipClaim.fetch(claim);
+      assert.equal(cancelled.isListed, false);
+    });
+  });
+  
+  describe("Organization Tests", () => {
+    const orgName = "acme-studio";
+    const findPda = (seeds) => anchor.web3.PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+    const organization = findPda([Buffer.from("organization"), Buffer.from(orgName)]);
+    
+    it("Create an organization and register a claim paid by a member", async () => {
+      const fileHash = "0a1b2c3d4e5f6789012345678901234567890123456789012345678901234567";
+      const orgMember = findPda([Buffer.from("org_member"), organization.toBuffer(), user.publicKey.toBuffer()]);
+      
+      await program.methods
+        .createOrganization(orgName)
+        .accounts({
+          organization,
+          orgStats: findPda([Buffer.from("org_stats"), organization.toBuffer()]),
+          orgTreasury: findPda([Buffer.from("org_treasury"), organization.toBuffer()]),
+          config: mscTokenConfig,
+          mint: mscMint,
+          creator: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+      
+      await program.methods
+        .addOrgMember(user.publicKey)
+        .accounts({
+          organization,
+          orgMember,
+          admin: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+      
+      await program.methods
+        .payAndCreateOrgClaim(new anchor.BN(1000000), fileHash, { original: {} })
+        .accounts({
+          subscription: null,
+          paymentRecord: findPda([Buffer.from("payment"), organization.toBuffer(), Buffer.from(fileHash)]),
+          claim: findPda([Buffer.from("claim"), organization.toBuffer(), Buffer.from(fileHash)]),
+          organization,
+          orgMember,
+          orgStats: findPda([Buffer.from("org_stats"), organization.toBuffer()]),
+          serviceConfig: serviceConfigPda(0),
+          globalState: globalStatePda(),
+          slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+          ownerIndexHead: ownerIndexHeadPda(organization),
+          ownerIndex: ownerIndexPda(organization),
+          parentClaim: null,
+          config: mscTokenConfig,
+          payerTokenAccount: await getAssociatedTokenAddress(mscMint, user.publicKey),
+          treasuryTokenAccount: await getAssociatedTokenAddress(mscMint, authority.publicKey),
+          member: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      
+      const claim = await program.account.ownershipClaim.fetch(
+        findPda([Buffer.from("claim"), organization.toBuffer(), Buffer.from(fileHash)])
+      );
+      assert.equal(claim.owner.toString(), organization.toString());
+      
+      const stats = await program.account.orgStats.fetch(
+        findPda([Buffer.from("org_stats"), organization.toBuffer()])
+      );
+      assert.equal(stats.totalClaims, 1);
+    });
+    
+    it("Reject membership changes by non-admins", async () => {
+      const outsider = Keypair.generate();
+      
+      await expectError(
+        program.methods
+          .addOrgMember(outsider.publicKey)
+          .accounts({
+            organization,
+            orgMember: findPda([Buffer.from("org_member"), organization.toBuffer(), outsider.publicKey.toBuffer()]),
+            admin: user.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([user])
+          .rpc(),
+        "InvalidAuthority"
+      );
+    });
+    
+    it("Let organization admins, not members, manage organization claims", async () => {
+      const fileHash = "0a1b2c3d4e5f6789012345678901234567890123456789012345678901234567";
+      const claim = findPda([Buffer.from("claim"), organization.toBuffer(), Buffer.from(fileHash)]);
+      const metadata = findPda([Buffer.from("claim_metadata"), claim.toBuffer()]);
+      const metadataAccounts = (owner) => ({
+        claim,
+        metadata,
+        organization,
+        owner,
+        systemProgram: SystemProgram.programId,
+      });
+      
+      await expectError(
+        program.methods
+          .setClaimMetadata("Logo", "image/svg+xml", "ar://acme-logo", "All rights reserved")
+          .accounts(metadataAccounts(user.publicKey))
+          .signers([user])
+          .rpc(),
+        "InvalidAuthority"
+      );
+      
+      await program.methods
+        .setClaimMetadata("Logo", "image/svg+xml", "ar://acme-logo", "All rights reserved")
+        .accounts(metadataAccounts(authority.publicKey))
+        .signers([authority])
+        .rpc();
+      
+      const account = await program.account.claimMetadata.fetch(metadata);
+      assert.equal(account.title, "Logo");
+    });
+    
+    it("Pay organization listing proceeds only into the organization treasury", async () => {
+      const fileHash = "0a1b2c3d4e5f6789012345678901234567890123456789012345678901234567";
+      const claim = findPda([Buffer.from("claim"), organization.toBuffer(), Buffer.from(fileHash)]);
+      const listing = findPda([Buffer.from("listing"), claim.toBuffer()]);
+      const orgTreasury = findPda([Buffer.from("org_treasury"), organization.toBuffer()]);
+      const buyer = await createFundedUser();
+      
+      await program.methods
+        .listClaim(new anchor.BN(5000000), { msc: {} }, new anchor.BN(0), [])
+        .accounts({
+          organization,
+          listing,
+          claim,
+          config: mscTokenConfig,
+          exchangePool: null,
+          owner: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+      
+      const purchaseAccounts = async (sellerTokenAccount) => ({
+        listing,
+        claim,
+        serviceConfig: serviceConfigPda(0),
+        buyerTokenAccount: await getAssociatedTokenAddress(mscMint, buyer.publicKey),
+        sellerTokenAccount,
+        treasuryTokenAccount: await getAssociatedTokenAddress(mscMint, authority.publicKey),
+        seller: organization,
+        fromOwnerIndex: ownerIndexPda(organization),
+        toOwnerIndexHead: ownerIndexHeadPda(buyer.publicKey),
+        toOwnerIndex: ownerIndexPda(buyer.publicKey),
+        buyer: buyer.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      });
+      
+      // 组织 PDA 名下的其他代币账户无法经 withdraw_org_treasury 取出
+      const orgAta = await createAssociatedTokenAccount(
+        connection,
+        buyer,
+        mscMint,
+        organization,
+        undefined,
+        undefined,
+        undefined,
+        true
+      );
+      await expectError(
+        program.methods
+          .purchaseListing()
+          .accounts(await purchaseAccounts(orgAta))
+          .signers([buyer])
+          .rpc(),
+        "InvalidRecipient"
+      );
+      
+      await program.methods
+        .purchaseListing()
+        .accounts(await purchaseAccounts(orgTreasury))
+        .signers([buyer])
+        .rpc();
+      
+      const treasury = await getAccount(connection, orgTreasury);
+      assert.equal(treasury.amount.toString(), "4875000");
+      const sold = await program.account.ownershipClaim.fetch(claim);
+      assert.equal(sold.owner.toString(), buyer.publicKey.toString());
+    });
+  });
+  
+  describe("Claim Metadata Tests", () => {
+    const fileHash = "a1b2c3d4e5f67890123456789012345678901234567890123456789012345678";
+    
+    it("Set and update claim metadata", async () => {
+      const [claimPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("claim"), user.publicKey.toBuffer(), Buffer.from(fileHash)],
+        program.programId
+      );
+      const [metadataPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("claim_metadata"), claimPda.toBuffer()],
+        program.programId
+      );
+      
+      await program.methods
+        .setClaimMetadata("Sunset", "image/png", "ar://sunset", "CC-BY-4.0")
+        .accounts({
+          organization: null,
+          claim: claimPda,
+          metadata: metadataPda,
+          owner: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      
+      await program.methods
+        .updateClaimMetadata("Sunset (final cut)", "image/png", "ipfs://bafy-sunset", "CC-BY-SA-4.0")
+        .accounts({
+          organization: null,
+          claim: claimPda,
+          metadata: metadataPda,
+          owner: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      
+      const metadata = await program.account.claimMetadata.fetch(metadataPda);
+      assert.equal(metadata.version, 2);
+      assert.equal(metadata.title, "Sunset (final cut)");
+      assert.equal(metadata.license, "CC-BY-SA-4.0");
+    });
+    
+    it("Reject invalid content type", async () => {
+      const [claimPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("claim"), user.publicKey.toBuffer(), Buffer.from(fileHash)],
+        program.programId
+      );
+      const [metadataPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("claim_metadata"), claimPda.toBuffer()],
+        program.programId
+      );
+      
+      await expectError(
+        program.methods
+          .updateClaimMetadata("Sunset", "png", "ar://sunset", "CC-BY-4.0")
+          .accounts({
+            organization: null,
+            claim: claimPda,
+            metadata: metadataPda,
+            owner: user.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([user])
+          .rpc(),
+        "InvalidContentType"
+      );
+    });
+  });
+  
+  describe("Co-ownership Tests", () => {
+    const fileHash = "a1b2c3d4e5f67890123456789012345678901234567890123456789012345678";
+    
+    it("Reject co-owner shares that do not sum to 10000", async () => {
+      const coAuthor = Keypair.generate();
+      const [claimPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("claim"), user.publicKey.toBuffer(), Buffer.from(fileHash)],
+        program.programId
+      );
+      const [coOwnersPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("co_owners"), claimPda.toBuffer()],
+        program.programId
+      );
+      
+      await expectError(
+        program.methods
+          .setCoOwners(
+            [
+              { owner: user.publicKey, shareBps: 6000 },
+              { owner: coAuthor.publicKey, shareBps: 3000 },
+            ],
+            2
+          )
+          .accounts({
+            claim: claimPda,
+            coOwners: coOwnersPda,
+            owner: user.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([user])
+          .rpc(),
+        "InvalidShareDistribution"
+      );
+    });
+    
+    it("Set co-owners and transfer a share", async () => {
+      const coAuthor = Keypair.generate();
+      const buyer = Keypair.generate();
+      const [claimPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("claim"), user.publicKey.toBuffer(), Buffer.from(fileHash)],
+        program.programId
+      );
+      const [coOwnersPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("co_owners"), claimPda.toBuffer()],
+        program.programId
+      );
+      
+      await program.methods
+        .setCoOwners(
+          [
+            { owner: user.publicKey, shareBps: 7000 },
+            { owner: coAuthor.publicKey, shareBps: 3000 },
+          ],
+          2
+        )
+        .accounts({
+          claim: claimPda,
+          coOwners: coOwnersPda,
+          owner: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      
+      await program.methods
+        .transferShare(2000, buyer.publicKey)
+        .accounts({
+          claim: claimPda,
+          coOwners: coOwnersPda,
+          coOwner: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+      
+      const coOwners = await program.account.claimCoOwners.fetch(coOwnersPda);
+      assert.equal(coOwners.coOwners.length, 3);
+      assert.equal(coOwners.coOwners[0].shareBps, 5000);
+      
+      // 共有后原所有者不能再单独编辑元数据
+      await expectError(
+        program.methods
+          .updateClaimMetadata("Sunset (co-owned)", "image/png", "ar://sunset", "CC-BY-4.0")
+          .accounts({
+            organization: null,
+            claim: claimPda,
+            metadata: anchor.web3.PublicKey.findProgramAddressSync(
+              [Buffer.from("claim_metadata"), claimPda.toBuffer()],
+              program.programId
+            )[0],
+            owner: user.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([user])
+          .rpc(),
+        "ClaimCoOwned"
+      );
+    });
+  });
+  
+  describe("Claim Lineage Tests", () => {
+    it("Reject a revision without a parent claim", async () => {
+      const revisionHash = "b1b2c3d4e5f67890123456789012345678901234567890123456789012345678";
+      await expectError(
+        program.methods
+          .payAndCreateClaim(new anchor.BN(1000000), revisionHash, { revision: {} })
+          .accounts(await payAndCreateClaimAccounts(user.publicKey, revisionHash))
+          .signers([user])
+          .rpc(),
+        "ParentClaimRequired"
+      );
+    });
+  });
+  
+  describe("Notarized Claim Tests", () => {
+    const notary = Keypair.generate();
+    
+    const notaryPda = () =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("notary"), notary.publicKey.toBuffer()],
+        program.programId
+      )[0];
+    
+    it("Add notary to allow-list", async () => {
+      await program.methods
+        .addNotary(notary.publicKey)
+        .accounts({
+          config: mscTokenConfig,
+          notaryEntry: notaryPda(),
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+      
+      const entry = await program.account.notary.fetch(notaryPda());
+      assert.equal(entry.notary.toString(), notary.publicKey.toString());
+    });
+    
+    it("Create notarized claim with ed25519 attestation", async () => {
+      const fileHash = "c1b2c3d4e5f67890123456789012345678901234567890123456789012345678";
+      const attestedAt = Math.floor(Date.now() / 1000);
+      const timestamp = Buffer.alloc(8);
+      timestamp.writeBigInt64LE(BigInt(attestedAt));
+      const message = Buffer.concat([
+        program.programId.toBuffer(),
+        Buffer.from("MSC_NOTARIZED_CLAIM_V1"),
+        user.publicKey.toBuffer(),
+        Buffer.from(fileHash),
+        timestamp,
+      ]);
+      
+      const ed25519Ix = anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: notary.secretKey,
+        message,
+      });
+      
+      await program.methods
+        .payAndCreateNotarizedClaim(new anchor.BN(1000000), fileHash, { original: {} }, new anchor.BN(attestedAt))
+        .accounts({
+          subscription: null,
+          paymentRecord: claimPdaFor("payment", user.publicKey, fileHash),
+          claim: claimPdaFor("claim", user.publicKey, fileHash),
+          config: mscTokenConfig,
+          serviceConfig: serviceConfigPda(0),
+          globalState: globalStatePda(),
+          userStats: anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("user_stats"), user.publicKey.toBuffer()],
+            program.programId
+          )[0],
+          slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+          ownerIndexHead: ownerIndexHeadPda(user.publicKey),
+          ownerIndex: ownerIndexPda(user.publicKey),
+          parentClaim: null,
+          notaryEntry: notaryPda(),
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          userTokenAccount: await getAssociatedTokenAddress(mscMint, user.publicKey),
+          treasuryTokenAccount: await getAssociatedTokenAddress(mscMint, authority.publicKey),
+          user: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([ed25519Ix])
+        .signers([user])
+        .rpc();
+      
+      const claim = await program.account.ownershipClaim.fetch(claimPdaFor("claim", user.publicKey, fileHash));
+      assert.equal(claim.notary.toString(), notary.publicKey.toString());
+    });
+  });
+  
+  describe("Commit-Reveal Claim Tests", () => {
+    const crypto = require("crypto");
+    const fileHash = "c0".repeat(32);
+    const salt = crypto.randomBytes(32);
+    
+    it("Commit then reveal a claim with the commit timestamp", async () => {
+      const commitmentHash = crypto
+        .createHash("sha256")
+        .update(Buffer.concat([Buffer.from(fileHash), user.publicKey.toBuffer(), salt]))
+        .digest();
+      const [claimCommitment] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("claim_commitment"), user.publicKey.toBuffer(), commitmentHash],
+        program.programId
+      );
+      const [escrowTokenAccount] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("commitment_escrow"), claimCommitment.toBuffer()],
+        program.programId
+      );
+      const [escrowAuthority] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow_authority")],
+        program.programId
+      );
+      const userTokenAccount = await getAssociatedTokenAddress(mscMint, user.publicKey);
+      
+      await program.methods
+        .commitClaim(Array.from(commitmentHash), new anchor.BN(1000000))
+        .accounts({
+          subscription: null,
+          claimCommitment,
+          serviceConfig: serviceConfigPda(0),
+          globalState: globalStatePda(),
+          config: mscTokenConfig,
+          mint: mscMint,
+          escrowTokenAccount,
+          escrowAuthority,
+          userTokenAccount,
+          slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+          user: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      
+      const committed = await program.account.claimCommitment.fetch(claimCommitment);
+      const escrow = await getAccount(connection, escrowTokenAccount);
+      assert.equal(escrow.amount.toString(), committed.amountPaid.toString());
+      
+      // 揭示窗口内不能关闭承诺
+      await expectError(
+        program.methods
+          .expireCommitment()
+          .accounts({
+            claimCommitment,
+            escrowTokenAccount,
+            escrowAuthority,
+            ownerTokenAccount: userTokenAccount,
+            owner: user.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc(),
+        "CommitmentNotExpired"
+      );
+      
+      const revealAccounts = {
+        claimCommitment,
+        paymentRecord: claimPdaFor("payment", user.publicKey, fileHash),
+        claim: claimPdaFor("claim", user.publicKey, fileHash),
+        globalState: globalStatePda(),
+        userStats: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("user_stats"), user.publicKey.toBuffer()],
+          program.programId
+        )[0],
+        ownerIndexHead: ownerIndexHeadPda(user.publicKey),
+        ownerIndex: ownerIndexPda(user.publicKey),
+        parentClaim: null,
+        serviceConfig: serviceConfigPda(0),
+        escrowTokenAccount,
+        escrowAuthority,
+        treasuryTokenAccount: await getAssociatedTokenAddress(mscMint, authority.publicKey),
+        user: user.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      };
+      
+      // 错误的 salt 无法揭示
+      await expectError(
+        program.methods
+          .revealClaim(fileHash, Array.from(crypto.randomBytes(32)), { original: {} })
+          .accounts(revealAccounts)
+          .signers([user])
+          .rpc(),
+        "CommitmentMismatch"
+      );
+      
+      await program.methods
+        .revealClaim(fileHash, Array.from(salt), { original: {} })
+        .accounts(revealAccounts)
+        .signers([user])
+        .rpc();
+      
+      const claim = await program.account.ownershipClaim.fetch(claimPdaFor("claim", user.publicKey, fileHash));
+      assert.equal(claim.fileHash, fileHash);
+      assert.equal(claim.timestamp.toString(), committed.committedAt.toString());
+      assert.equal(claim.slot.toString(), committed.committedSlot.toString());
+      assert.equal(claim.recentSlot.toString(), committed.recentSlot.toString());
+      assert.equal(claim.paymentSequence.toString(), committed.paymentSequence.toString());
+    });
+  });
+  
+  describe("Relayed Claim Tests", () => {
+    it("Relayer pays rent and is reimbursed in MSC", async () => {
+      const fileHash = "d1b2c3d4e5f67890123456789012345678901234567890123456789012345678";
+      const gaslessUser = Keypair.generate(); // holds MSC but no SOL
+      const relayerFee = 100000; // 0.1 MSC
+      
+      await program.methods
+        .relayedPayAndCreateClaim(new anchor.BN(1000000), fileHash, { original: {} }, new anchor.BN(relayerFee))
+        .accounts({
+          subscription: null,
+          paymentRecord: claimPdaFor("payment", gaslessUser.publicKey, fileHash),
+          claim: claimPdaFor("claim", gaslessUser.publicKey, fileHash),
+          config: mscTokenConfig,
+          serviceConfig: serviceConfigPda(0),
+          globalState: globalStatePda(),
+          userStats: anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("user_stats"), gaslessUser.publicKey.toBuffer()],
+            program.programId
+          )[0],
+          slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+          ownerIndexHead: ownerIndexHeadPda(gaslessUser.publicKey),
+          ownerIndex: ownerIndexPda(gaslessUser.publicKey),
+          parentClaim: null,
+          userTokenAccount: await getAssociatedTokenAddress(mscMint, gaslessUser.publicKey),
+          treasuryTokenAccount: await getAssociatedTokenAddress(mscMint, authority.publicKey),
+          relayerTokenAccount: await getAssociatedTokenAddress(mscMint, authority.publicKey),
+          user: gaslessUser.publicKey,
+          relayer: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([gaslessUser, authority])
+        .rpc();
+      
+      const claim = await program.account.ownershipClaim.fetch(claimPdaFor("claim", gaslessUser.publicKey, fileHash));
+      assert.equal(claim.owner.toString(), gaslessUser.publicKey.toString());
+    });
+  });
+  
+  describe("Batch Claim Tests", () => {
+    const crypto = require("crypto");
+    const sha256 = (...parts) => crypto.createHash("sha256").update(Buffer.concat(parts)).digest();
+    const leaf = (fileHash) => sha256(Buffer.from([0]), Buffer.from(fileHash));
+    const node = (a, b) => (Buffer.compare(a, b) <= 0 ? sha256(Buffer.from([1]), a, b) : sha256(Buffer.from([1]), b, a));
+    
+    const fileHashes = [
+      "e1b2c3d4e5f67890123456789012345678901234567890123456789012345678",
+      "e2b2c3d4e5f67890123456789012345678901234567890123456789012345678",
+    ];
+    const merkleRoot = node(leaf(fileHashes[0]), leaf(fileHashes[1]));
+    
+    it("Create batch claim and verify membership", async () => {
+      const [batchClaimPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("batch_claim"), user.publicKey.toBuffer(), merkleRoot],
+        program.programId
+      );
+      
+      await program.methods
+        .createBatchClaim(Array.from(merkleRoot), fileHashes.length, new anchor.BN(2000000))
+        .accounts({
+          subscription: null,
+          batchClaim: batchClaimPda,
+          globalState: globalStatePda(),
+          slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+          config: mscTokenConfig,
+          serviceConfig: serviceConfigPda(0),
+          userTokenAccount: await getAssociatedTokenAddress(mscMint, user.publicKey),
+          treasuryTokenAccount: await getAssociatedTokenAddress(mscMint, authority.publicKey),
+          user: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      
+      await program.methods
+        .verifyBatchMembership(fileHashes[0], [Array.from(leaf(fileHashes[1]))])
+        .accounts({ batchClaim: batchClaimPda })
+        .rpc();
+      
+      const batch = await program.account.batchClaim.fetch(batchClaimPda);
+      assert.equal(batch.count, 2);
+      assert.equal(batch.depth, 1);
+      assert.ok(batch.sequence.toNumber() > 0);
+      assert.ok(batch.recentSlot.lte(batch.slot));
+    });
+    
+    it("Reject proofs longer than the batch tree depth", async () => {
+      const [batchClaimPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("batch_claim"), user.publicKey.toBuffer(), merkleRoot],
+        program.programId
+      );
+      
+      await expectError(
+        program.methods
+          .verifyBatchMembership(fileHashes[0], [
+            Array.from(leaf(fileHashes[1])),
+            Array.from(leaf(fileHashes[0])),
+          ])
+          .accounts({ batchClaim: batchClaimPda })
+          .rpc(),
+        "InvalidMerkleProof"
+      );
+    });
+  });
+  
+  describe("Compressed Claim Tests", () => {
+    // Requires spl_account_compression.so and spl_noop.so loaded via [[test.genesis]] (yarn fixtures)
+    const { keccak_256 } = require("@noble/hashes/sha3");
+    const COMPRESSION_PROGRAM_ID = new anchor.web3.PublicKey("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+    const NOOP_PROGRAM_ID = new anchor.web3.PublicKey("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+    const maxDepth = 3;
+    const maxBufferSize = 8;
+    const merkleTree = Keypair.generate();
+    const fileHash = "f1b2c3d4e5f67890123456789012345678901234567890123456789012345678";
+    
+    // Concurrent Merkle tree account size (no canopy)
+    const treeAccountSize = (depth, bufferSize) => {
+      const changeLog = 32 + 32 * depth + 4 + 4;
+      const path = 32 * depth + 32 + 4 + 4;
+      return 1 + 54 + 8 + 8 + 8 + bufferSize * changeLog + path;
+    };
+    const hashPair = (left, right) => Buffer.from(keccak_256(Buffer.concat([left, right])));
+    const emptyNodes = [Buffer.alloc(32)];
+    for (let i = 1; i <= maxDepth; i++) emptyNodes.push(hashPair(emptyNodes[i - 1], emptyNodes[i - 1]));
+    
+    const claimLeaf = (claim) => {
+      const u64 = (value) => {
+        const bytes = Buffer.alloc(8);
+        bytes.writeBigUInt64LE(BigInt(value.toString()));
+        return bytes;
+      };
+      const timestamp = Buffer.alloc(8);
+      timestamp.writeBigInt64LE(BigInt(claim.timestamp.toString()));
+      const index = Buffer.alloc(4);
+      index.writeUInt32LE(claim.leafIndex);
+      return Buffer.from(
+        keccak_256(
+          Buffer.concat([
+            claim.owner.toBuffer(),
+            Buffer.from(claim.fileHash),
+            timestamp,
+            index,
+            u64(claim.sequence),
+            u64(claim.slot),
+            u64(claim.recentSlot),
+            Buffer.from(claim.recentSlotHash),
+            Buffer.from([claim.isActive ? 1 : 0]),
+          ])
+        )
+      );
+    };
+    
+    const [claimTreePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("claim_tree"), merkleTree.publicKey.toBuffer()],
+      program.programId
+    );
+    
+    it("Create claim tree and append a compressed claim", async () => {
+      const space = treeAccountSize(maxDepth, maxBufferSize);
+      const allocIx = SystemProgram.createAccount({
+        fromPubkey: authority.publicKey,
+        newAccountPubkey: merkleTree.publicKey,
+        lamports: await connection.getMinimumBalanceForRentExemption(space),
+        space,
+        programId: COMPRESSION_PROGRAM_ID,
+      });
+      
+      await program.methods
+        .createClaimTree(maxDepth, maxBufferSize)
+        .accounts({
+          config: mscTokenConfig,
+          claimTree: claimTreePda,
+          merkleTree: merkleTree.publicKey,
+          authority: authority.publicKey,
+          compressionProgram: COMPRESSION_PROGRAM_ID,
+          noopProgram: NOOP_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([allocIx])
+        .signers([authority, merkleTree])
+        .rpc();
+      
+      await program.methods
+        .payAndCreateCompressedClaim(new anchor.BN(1000000), fileHash)
+        .accounts({
+          subscription: null,
+          claimTree: claimTreePda,
+          merkleTree: merkleTree.publicKey,
+          globalState: globalStatePda(),
+          slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+          config: mscTokenConfig,
+          serviceConfig: serviceConfigPda(0),
+          userTokenAccount: await getAssociatedTokenAddress(mscMint, user.publicKey),
+          treasuryTokenAccount: await getAssociatedTokenAddress(mscMint, authority.publicKey),
+          user: user.publicKey,
+          compressionProgram: COMPRESSION_PROGRAM_ID,
+          noopProgram: NOOP_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+      
+      const claimTree = await program.account.claimTree.fetch(claimTreePda);
+      assert.equal(claimTree.numClaims.toString(), "1");
+    });
+    
+    it("Transfer compressed claim with proof", async () => {
+      // Leaf data is normally recovered by an indexer from the noop event
+      const signatures = await connection.getSignaturesForAddress(merkleTree.publicKey, { limit: 1 });
+      const tx = await connection.getTransaction(signatures[0].signature, { maxSupportedTransactionVersion: 0 });
+      const accountKeys = tx.transaction.message.getAccountKeys();
+      const eventData = tx.meta.innerInstructions
+        .flatMap((inner) => inner.instructions)
+        .find((ix) => accountKeys.get(ix.programIdIndex).equals(NOOP_PROGRAM_ID)).data;
+      // CompressedClaimEvent = merkle_tree (32) || action (1) || claim
+      const claim = program.coder.types.decode(
+        "CompressedClaim",
+        Buffer.from(anchor.utils.bytes.bs58.decode(eventData)).subarray(33)
+      );
+      assert.equal(claim.fileHash, fileHash);
+      assert.ok(claim.sequence.toNumber() > 0);
+      assert.ok(claim.recentSlot.lte(claim.slot));
+      
+      // Single leaf at index 0: proof is the empty subtree at every level
+      let root = claimLeaf(claim);
+      for (let i = 0; i < maxDepth; i++) root = hashPair(root, emptyNodes[i]);
+      const proof = emptyNodes.slice(0, maxDepth).map((node) => ({
+        pubkey: new anchor.web3.PublicKey(node),
+        isSigner: false,
+        isWritable: false,
+      }));
+      
+      const newOwner = Keypair.generate().publicKey;
+      await program.methods
+        .transferCompressedClaim(Array.from(root), claim, newOwner)
+        .accounts({
+          claimTree: claimTreePda,
+          merkleTree: merkleTree.publicKey,
+          owner: user.publicKey,
+          compressionProgram: COMPRESSION_PROGRAM_ID,
+          noopProgram: NOOP_PROGRAM_ID,
+        })
+        .remainingAccounts(proof)
+        .signers([user])
+        .rpc();
+    });
+  });
+  
   describe("Service Contract Tests", () => {
     it("Pay with MSC for Service", async () => {
       const paymentAmount = 10000000; // 10 MSC
@@ -195,6 +1822,73 @@ describe("MSC Contracts", () => {
         // Expected to fail due to token account setup in test environment
       }
     });
+    
+    it("Escrow service payment and refund it", async () => {
+      const paymentId = new anchor.BN(1);
+      const [paymentRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("service_payment"), user.publicKey.toBuffer(), paymentId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [escrowTokenAccount] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("payment_escrow"), paymentRecord.toBuffer()],
+        program.programId
+      );
+      const [escrowAuthority] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow_authority")],
+        program.programId
+      );
+      const userTokenAccount = await getAssociatedTokenAddress(mscMint, user.publicKey);
+      
+      await program.methods
+        .payForService(2, paymentId, new anchor.BN(500000))
+        .accounts({
+          paymentRecord,
+          config: mscTokenConfig,
+          serviceConfig: serviceConfigPda(2),
+          globalState: globalStatePda(),
+          escrowTokenAccount,
+          escrowAuthority,
+          mint: mscMint,
+          userTokenAccount,
+          payer: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      
+      let record = await program.account.paymentRecord.fetch(paymentRecord);
+      assert.equal(record.status, 0);
+      
+      const refundAccounts = {
+        config: mscTokenConfig,
+        paymentRecord,
+        escrowTokenAccount,
+        escrowAuthority,
+        payerTokenAccount: userTokenAccount,
+        payer: user.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      };
+      
+      // 付款人需等待超时
+      await expectError(
+        program.methods
+          .refundPayment()
+          .accounts({ ...refundAccounts, authority: user.publicKey })
+          .signers([user])
+          .rpc(),
+        "RefundNotAvailable"
+      );
+      
+      await program.methods
+        .refundPayment()
+        .accounts({ ...refundAccounts, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+      
+      record = await program.account.paymentRecord.fetch(paymentRecord);
+      assert.equal(record.status, 2);
+    });
   });
   
   describe("Exchange Contract Tests", () => {
@@ -208,28 +1902,33 @@ describe("MSC Contracts", () => {
         6 // USDC decimals
       );
       
-      const exchangePoolKeypair = Keypair.generate();
-      const mscVault = Keypair.generate();
-      const usdcVault = Keypair.generate();
+      const [exchangePoolPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("exchange_pool")],
+        program.programId
+      );
       
       try {
+        const mscVault = await createAccount(connection, authority, mscMint, authority.publicKey, Keypair.generate());
+        const usdcVault = await createAccount(connection, authority, usdcMint, authority.publicKey, Keypair.generate());
+        
         const tx = await program.methods
           .initializeExchangePool()
           .accounts({
-            exchangePool: exchangePoolKeypair.publicKey,
+            config: mscTokenConfig,
+            exchangePool: exchangePoolPda,
             mscMint: mscMint,
             usdcMint: usdcMint,
-            mscVault: mscVault.publicKey,
-            usdcVault: usdcVault.publicKey,
+            mscVault: mscVault,
+            usdcVault: usdcVault,
             authority: authority.publicKey,
             systemProgram: SystemProgram.programId,
           })
-          .signers([authority, exchangePoolKeypair])
+          .signers([authority])
           .rpc();
         
         console.log("Exchange pool initialized:", tx);
         
-        exchangePool = exchangePoolKeypair.publicKey;
+        exchangePool = exchangePoolPda;
       } catch (error) {
         console.log("Initialize Exchange Pool Error:", error);
         // Expected to fail due to account constraints
@@ -261,6 +1960,419 @@ describe("MSC Contracts", () => {
     });
   });
   
+  describe("Account Constraint Tests", () => {
+    let foreignMint;
+    let foreignTokenAccount;
+    let authorityTokenAccount;
+    let userTokenAccount;
+    
+    before(async () => {
+      foreignMint = await createMint(connection, authority, authority.publicKey, null, 6);
+      foreignTokenAccount = await createAccount(connection, authority, foreignMint, authority.publicKey, Keypair.generate());
+      authorityTokenAccount = await getAssociatedTokenAddress(mscMint, authority.publicKey);
+      userTokenAccount = await getAssociatedTokenAddress(mscMint, user.publicKey);
+    });
+    
+    it("Reject mint_msc with a mint other than config.mint", async () => {
+      await expectError(
+        program.methods
+          .mintMsc(new anchor.BN(1))
+          .accounts({
+            config: mscTokenConfig,
+            mint: foreignMint,
+            to: foreignTokenAccount,
+            authority: authority.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([authority])
+          .rpc(),
+        "InvalidMint"
+      );
+    });
+    
+    it("Reject mint_msc into a non-MSC token account", async () => {
+      await expectError(
+        program.methods
+          .mintMsc(new anchor.BN(1))
+          .accounts({
+            config: mscTokenConfig,
+            mint: mscMint,
+            to: foreignTokenAccount,
+            authority: authority.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([authority])
+          .rpc(),
+        "InvalidMint"
+      );
+    });
+    
+    it("Reject transfer_msc from a non-MSC token account", async () => {
+      await expectError(
+        program.methods
+          .transferMsc(new anchor.BN(1))
+          .accounts({
+            config: mscTokenConfig,
+            from: foreignTokenAccount,
+            to: userTokenAccount,
+            authority: authority.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([authority])
+          .rpc(),
+        "InvalidMint"
+      );
+    });
+    
+    it("Reject transfer_msc to a non-MSC token account", async () => {
+      await expectError(
+        program.methods
+          .transferMsc(new anchor.BN(1))
+          .accounts({
+            config: mscTokenConfig,
+            from: authorityTokenAccount,
+            to: foreignTokenAccount,
+            authority: authority.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([authority])
+          .rpc(),
+        "InvalidMint"
+      );
+    });
+    
+    it("Reject transfer_msc from an account the signer does not own", async () => {
+      await expectError(
+        program.methods
+          .transferMsc(new anchor.BN(1))
+          .accounts({
+            config: mscTokenConfig,
+            from: userTokenAccount,
+            to: authorityTokenAccount,
+            authority: authority.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([authority])
+          .rpc(),
+        "InvalidAccountOwner"
+      );
+    });
+    
+    it("Reject batch_airdrop to a non-MSC token account", async () => {
+      await expectError(
+        program.methods
+          .batchAirdrop([new anchor.BN(1)])
+          .accounts({
+            config: mscTokenConfig,
+            from: authorityTokenAccount,
+            to: foreignTokenAccount,
+            authority: authority.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([authority])
+          .rpc(),
+        "InvalidMint"
+      );
+    });
+    
+    it("Reject batch_airdrop from an account the signer does not own", async () => {
+      await expectError(
+        program.methods
+          .batchAirdrop([new anchor.BN(1)])
+          .accounts({
+            config: mscTokenConfig,
+            from: userTokenAccount,
+            to: authorityTokenAccount,
+            authority: authority.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([authority])
+          .rpc(),
+        "InvalidAccountOwner"
+      );
+    });
+    
+    it("Reject exchange pool vault with the wrong mint", async () => {
+      const usdcVault = await createAccount(connection, authority, usdcMint, authority.publicKey, Keypair.generate());
+      await expectError(
+        program.methods
+          .initializeExchangePool()
+          .accounts({
+            config: mscTokenConfig,
+            exchangePool: exchangePool,
+            mscMint: mscMint,
+            usdcMint: usdcMint,
+            mscVault: foreignTokenAccount,
+            usdcVault,
+            authority: authority.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority])
+          .rpc(),
+        "InvalidMint"
+      );
+    });
+    
+    describe("pay_for_service / refund_payment", () => {
+      const paymentId = new anchor.BN(2);
+      const [paymentRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("service_payment"), user.publicKey.toBuffer(), paymentId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [escrowTokenAccount] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("payment_escrow"), paymentRecord.toBuffer()],
+        program.programId
+      );
+      const [escrowAuthority] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow_authority")],
+        program.programId
+      );
+      
+      const payForService = (overrides) =>
+        program.methods
+          .payForService(2, paymentId, new anchor.BN(500000))
+          .accounts({
+            paymentRecord,
+            serviceConfig: serviceConfigPda(2),
+            globalState: globalStatePda(),
+            escrowTokenAccount,
+            escrowAuthority,
+            config: mscTokenConfig,
+            mint: mscMint,
+            userTokenAccount,
+            payer: user.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            ...overrides,
+          })
+          .signers([user])
+          .rpc();
+      
+      it("Reject a mint other than config.mint", async () => {
+        await expectError(payForService({ mint: foreignMint }), "InvalidMint");
+      });
+      
+      it("Reject a payer token account with the wrong mint", async () => {
+        const userForeignAccount = await createAccount(connection, user, foreignMint, user.publicKey, Keypair.generate());
+        await expectError(payForService({ userTokenAccount: userForeignAccount }), "InvalidMint");
+      });
+      
+      it("Reject a payer token account the payer does not own", async () => {
+        await expectError(payForService({ userTokenAccount: authorityTokenAccount }), "InvalidAccountOwner");
+      });
+      
+      it("Reject a refund to an account the payer does not own", async () => {
+        await payForService({});
+        await expectError(
+          program.methods
+            .refundPayment()
+            .accounts({
+              config: mscTokenConfig,
+              paymentRecord,
+              escrowTokenAccount,
+              escrowAuthority,
+              payerTokenAccount: authorityTokenAccount,
+              payer: user.publicKey,
+              authority: authority.publicKey,
+              tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([authority])
+            .rpc(),
+          "InvalidRecipient"
+        );
+      });
+    });
+    
+    describe("exchange_pay_and_create_claim", () => {
+      const fileHash = "e1b2c3d4e5f67890123456789012345678901234567890123456789012345678";
+      const payer = Keypair.generate(); // holds USDC but no MSC
+      let pool;
+      let payerUsdcAccount;
+      
+      const findPda = (seeds) => anchor.web3.PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+      
+      const exchangePay = async (maxUsdcAmount) => {
+        const serviceConfig = await program.account.serviceConfig.fetch(serviceConfigPda(0));
+        return program.methods
+          .exchangePayAndCreateClaim(new anchor.BN(maxUsdcAmount), fileHash, { original: {} })
+          .accounts({
+            subscription: null,
+            paymentRecord: findPda([Buffer.from("payment"), payer.publicKey.toBuffer(), Buffer.from(fileHash)]),
+            claim: findPda([Buffer.from("claim"), payer.publicKey.toBuffer(), Buffer.from(fileHash)]),
+            serviceConfig: serviceConfigPda(0),
+            globalState: globalStatePda(),
+            userStats: findPda([Buffer.from("user_stats"), payer.publicKey.toBuffer()]),
+            ownerIndexHead: ownerIndexHeadPda(payer.publicKey),
+            ownerIndex: ownerIndexPda(payer.publicKey),
+            slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+            parentClaim: null,
+            config: mscTokenConfig,
+            exchangePool: exchangePool,
+            poolMscVault: pool.mscVault,
+            poolUsdcVault: pool.usdcVault,
+            userUsdcAccount: payerUsdcAccount,
+            treasuryTokenAccount: serviceConfig.treasury,
+            user: payer.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([payer])
+          .rpc();
+      };
+      
+      before(async () => {
+        pool = await program.account.exchangePool.fetch(exchangePool);
+        const signature = await connection.requestAirdrop(payer.publicKey, 2 * LAMPORTS_PER_SOL);
+        await connection.confirmTransaction(signature);
+        payerUsdcAccount = await createAccount(connection, payer, usdcMint, payer.publicKey, Keypair.generate());
+        await mintTo(connection, authority, usdcMint, payerUsdcAccount, authority, 100000000);
+      });
+      
+      it("Reject when the pool MSC vault is not controlled by the pool", async () => {
+        await expectError(exchangePay(100000000), "InvalidAccountOwner");
+      });
+      
+      it("Reject when the USDC cost exceeds max_usdc_amount", async () => {
+        await mintTo(connection, authority, mscMint, pool.mscVault, authority, 100000000);
+        await setAuthority(connection, authority, pool.mscVault, authority, AuthorityType.AccountOwner, exchangePool);
+        await expectError(exchangePay(1), "PriceAboveMaximum");
+      });
+      
+      it("Pay in USDC while the treasury receives MSC", async () => {
+        const serviceConfig = await program.account.serviceConfig.fetch(serviceConfigPda(0));
+        const treasuryBefore = await getAccount(connection, serviceConfig.treasury);
+        
+        await exchangePay(100000000);
+        
+        const treasuryAfter = await getAccount(connection, serviceConfig.treasury);
+        assert.equal(
+          (treasuryAfter.amount - treasuryBefore.amount).toString(),
+          serviceConfig.price.toString()
+        );
+        const claim = await program.account.ownershipClaim.fetch(
+          findPda([Buffer.from("claim"), payer.publicKey.toBuffer(), Buffer.from(fileHash)])
+        );
+        assert.equal(claim.owner.toString(), payer.publicKey.toString());
+      });
+    });
+    
+    describe("swap_msc_to_usdc", () => {
+      let pool;
+      let userUsdcAccount;
+      
+      const swapAccounts = (overrides) => ({
+        exchangePool: exchangePool,
+        userMscAccount: userTokenAccount,
+        userUsdcAccount,
+        poolMscVault: pool.mscVault,
+        poolUsdcVault: pool.usdcVault,
+        poolAuthority: exchangePool,
+        user: user.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        ...overrides,
+      });
+      
+      const swap = (overrides) => {
+        const swapRecord = Keypair.generate();
+        const userStats = Keypair.generate();
+        return program.methods
+          .swapMscToUsdc(new anchor.BN(1000000))
+          .accounts(swapAccounts({ swapRecord: swapRecord.publicKey, userStats: userStats.publicKey, ...overrides }))
+          .signers([user, swapRecord, userStats])
+          .rpc();
+      };
+      
+      before(async () => {
+        pool = await program.account.exchangePool.fetch(exchangePool);
+        userUsdcAccount = await createAccount(connection, user, usdcMint, user.publicKey, Keypair.generate());
+      });
+      
+      it("Reject MSC vault that is not the pool vault", async () => {
+        await expectError(swap({ poolMscVault: authorityTokenAccount }), "InvalidRecipient");
+      });
+      
+      it("Reject USDC vault that is not the pool vault", async () => {
+        await expectError(swap({ poolUsdcVault: userUsdcAccount }), "InvalidRecipient");
+      });
+      
+      it("Reject USDC vault not controlled by the pool", async () => {
+        await expectError(swap({}), "InvalidAccountOwner");
+      });
+      
+      it("Reject pool authority other than the exchange pool PDA", async () => {
+        await setAuthority(connection, authority, pool.usdcVault, authority, AuthorityType.AccountOwner, exchangePool);
+        await expectError(swap({ poolAuthority: authority.publicKey }), "InvalidAuthority");
+      });
+      
+      it("Reject user MSC account with the wrong mint", async () => {
+        await expectError(swap({ userMscAccount: userUsdcAccount }), "InvalidMint");
+      });
+      
+      it("Reject user MSC account not owned by the user", async () => {
+        await expectError(swap({ userMscAccount: authorityTokenAccount }), "InvalidAccountOwner");
+      });
+      
+      it("Reject user USDC account with the wrong mint", async () => {
+        await expectError(swap({ userUsdcAccount: foreignTokenAccount }), "InvalidMint");
+      });
+      
+      it("Reject user USDC account not owned by the user", async () => {
+        const otherUsdcAccount = await createAccount(connection, authority, usdcMint, authority.publicKey, Keypair.generate());
+        await expectError(swap({ userUsdcAccount: otherUsdcAccount }), "InvalidAccountOwner");
+      });
+    });
+  });
+  
+  describe("Owner Index Tests", () => {
+    it("Index a new claim and move it to a recipient without prior setup", async () => {
+      const fileHash = "f1b2c3d4e5f67890123456789012345678901234567890123456789012345678";
+      const owner = await createFundedUser();
+      const recipient = Keypair.generate();
+      const claim = claimPdaFor("claim", owner.publicKey, fileHash);
+      
+      await program.methods
+        .payAndCreateClaim(new anchor.BN(100000000), fileHash, { original: {} })
+        .accounts(await payAndCreateClaimAccounts(owner.publicKey, fileHash))
+        .signers([owner])
+        .rpc();
+      
+      const head = await program.account.ownerIndexHead.fetch(ownerIndexHeadPda(owner.publicKey));
+      assert.equal(head.currentPage, 0);
+      let page = await program.methods
+        .getOwnerIndexPage()
+        .accounts({ ownerIndex: ownerIndexPda(owner.publicKey) })
+        .view();
+      assert.ok(page.claims.some((key) => key.equals(claim)));
+      
+      await program.methods
+        .transferClaim(recipient.publicKey)
+        .accounts({
+          organization: null,
+          claim,
+          fromOwnerIndex: ownerIndexPda(owner.publicKey),
+          toOwnerIndexHead: ownerIndexHeadPda(recipient.publicKey),
+          toOwnerIndex: ownerIndexPda(recipient.publicKey),
+          owner: owner.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+      
+      page = await program.methods
+        .getOwnerIndexPage()
+        .accounts({ ownerIndex: ownerIndexPda(owner.publicKey) })
+        .view();
+      assert.equal(page.claims.length, 0);
+      page = await program.methods
+        .getOwnerIndexPage()
+        .accounts({ ownerIndex: ownerIndexPda(recipient.publicKey) })
+        .view();
+      assert.equal(page.owner.toString(), recipient.publicKey.toString());
+      assert.ok(page.claims.some((key) => key.equals(claim)));
+    });
+  });
+  
   describe("Integration Tests", () => {
     it("Complete Workflow: Pay -> Claim -> Swap", async () => {
       console.log("=== Complete Workflow Test ===");